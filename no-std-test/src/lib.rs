@@ -0,0 +1,65 @@
+//! this crate makes sure that quick-builder and the generated code work in
+//! `no_std` crates. The library itself is `no_std`, while the tests in the
+//! tests folder use the standard library as usual. Without the `alloc`
+//! feature, it also builds for targets without an allocator, like
+//! `thumbv7em-none-eabihf`.
+#![no_std]
+
+#[cfg(feature = "alloc")]
+use quick_builder::SetError;
+use quick_builder::{DynBuildError, QuickBuilder};
+
+/// a sensor reading with invariants on the fields and the whole struct
+#[derive(Debug, Clone, PartialEq, QuickBuilder)]
+#[quick_builder(derive(Clone, Debug), dyn_builder)]
+#[cfg_attr(feature = "alloc", quick_builder(set_str))]
+#[invariant(|reading| reading.min <= reading.max)]
+pub struct Reading {
+    /// the lower bound
+    #[invariant(|min| *min >= -40)]
+    pub min: i16,
+    /// the upper bound
+    #[invariant(is_in_range, debug_only)]
+    pub max: i16,
+    /// the channel, which is clamped to the available ones
+    #[builder(transform = |channel: u8| channel.min(7))]
+    pub channel: u8,
+}
+
+fn is_in_range(max: &i16) -> bool {
+    *max <= 125
+}
+
+/// a struct without invariants that is built in a const context
+#[derive(Debug, Clone, Copy, PartialEq, QuickBuilder)]
+pub struct Pin<'a> {
+    /// the name of the pin
+    pub name: &'a str,
+    /// the number of the pin
+    pub number: u8,
+}
+
+/// the pin that the sensor is connected to
+pub const SENSOR_PIN: Pin<'static> = Pin::builder().name("sensor").number(3).build();
+
+/// build a reading with the typestate builder
+pub fn reading(min: i16, max: i16, channel: u8) -> Option<Reading> {
+    Reading::builder()
+        .min(min)
+        .max(max)
+        .channel(channel)
+        .build()
+}
+
+/// build a reading with the dynamic builder
+pub fn dyn_reading(min: i16, max: i16) -> Result<Reading, DynBuildError> {
+    let mut builder = Reading::dyn_builder();
+    builder.min(min).max(max).channel(0);
+    builder.try_build()
+}
+
+/// set the minimum of a reading from a string
+#[cfg(feature = "alloc")]
+pub fn set_min(min: &str) -> Result<(), SetError> {
+    Reading::dyn_builder().set_str("min", min)
+}
@@ -0,0 +1,41 @@
+use no_std_test::{dyn_reading, reading, Reading, SENSOR_PIN};
+use quick_builder::DynBuildError;
+
+#[test]
+fn typestate_builder_works_in_no_std() {
+    assert_eq!(
+        reading(-10, 30, 9),
+        Some(Reading {
+            min: -10,
+            max: 30,
+            channel: 7
+        })
+    );
+    assert_eq!(reading(-50, 30, 0), None);
+    assert_eq!(reading(40, 30, 0), None);
+    assert_eq!(SENSOR_PIN.number, 3);
+}
+
+#[test]
+fn dyn_builder_works_in_no_std() {
+    assert_eq!(dyn_reading(0, 10).map(|r| r.max), Ok(10));
+    assert_eq!(
+        dyn_reading(10, 0),
+        Err(DynBuildError::InvariantViolated(
+            "invariant of `Reading` is violated"
+        ))
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn set_str_works_in_no_std_with_alloc() {
+    use no_std_test::set_min;
+    use quick_builder::SetError;
+
+    assert_eq!(set_min("-3"), Ok(()));
+    assert!(matches!(
+        set_min("x"),
+        Err(SetError::Parse { field: "min", .. })
+    ));
+}
@@ -1,10 +1,29 @@
-use crate::{detail::StructDeriveInput, error::CompileError, validation::InvariantAttribute};
-use proc_macro2::Span;
+use crate::{
+    detail::{higher_ranked_bound, hygienic_ident, local_ident, NamedField, StructDeriveInput},
+    error::CompileError,
+    options::{BuilderOptions, FieldOptions},
+    validation::{check_invariant_bindings, InvariantAttribute},
+};
+use const_eval::has_no_drop_glue;
+use derives::make_builder_derives;
+use diagnostics::{make_misuse_diagnostics, BuildSignature};
+use docs::{build_docs, builder_docs, setter_docs};
+use dyn_builder::{make_dyn_builder, DynBuilderInput};
+use generators::{make_arbitrary, make_proptest_arbitrary, GeneratorInput};
+use proc_macro2::{Ident, Span, TokenStream};
 use quote::{format_ident, quote, quote_spanned, ToTokens};
-use special_generics::TypeGenericsWithoutAngleBrackets;
-use syn::{spanned::Spanned, Index};
+use special_generics::{without_defaults, TypeGenericsWithoutAngleBrackets};
+use stages::{make_fields_module, make_stage_aliases, make_stage_traits};
+use syn::{Expr, Index, Type};
 
+mod const_eval;
+mod derives;
+mod diagnostics;
+mod docs;
+mod dyn_builder;
+mod generators;
 mod special_generics;
+mod stages;
 
 /// the identifier for the finished value of the structure to build inside the
 /// builder method. We have a global constant because we want to verify that
@@ -26,18 +45,36 @@ impl ToTokens for Builder {
 pub fn make_builder(input: &StructDeriveInput) -> Result<Builder, CompileError> {
     let original_struct_ident = &input.ident;
     let builder_ident = format_ident!("{}Builder", original_struct_ident);
-    let builder_mod_ident = format_ident!("__{}Module", builder_ident);
-    let fields = match input.data.fields {
-        syn::Fields::Named(ref named) => &named.named,
-        _ => unreachable!("struct must only have named fields"),
-    };
+    let fields = &input.fields;
 
-    if fields.is_empty() {
-        return Err(CompileError::new_spanned(
-            &input.ident,
-            "QuickBuilder: not possible to derive on struct without fields",
-        ));
-    }
+    // the options given in #[quick_builder(...)] on the struct
+    let options = BuilderOptions::new(&input.attrs)?;
+    let runtime_crate = options.runtime_crate();
+
+    // the options given in #[builder(...)] on the individual fields, in
+    // 1-to-1 correspondence with the fields.
+    let field_options = fields
+        .iter()
+        .map(FieldOptions::new)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // the fields that get a setter on the builder, in order of declaration.
+    // The skipped fields don't take part in the builder state, they are
+    // computed in the build function instead.
+    let set_fields = fields
+        .iter()
+        .zip(field_options.iter())
+        .filter(|(_, options)| options.skip().is_none())
+        .map(|(field, _)| field.clone())
+        .collect::<Vec<_>>();
+    let set_fields = &set_fields;
+    // the transform functions of the set fields, in 1-to-1 correspondence
+    // with the set fields.
+    let set_field_transforms = field_options
+        .iter()
+        .filter(|options| options.skip().is_none())
+        .map(FieldOptions::transform)
+        .collect::<Vec<_>>();
 
     // the validate attribut on the struct itself, if any
     let struct_validate_attribute = InvariantAttribute::new(&input.attrs)?;
@@ -50,6 +87,15 @@ pub fn make_builder(input: &StructDeriveInput) -> Result<Builder, CompileError>
         .map(|f| InvariantAttribute::new(&f.attrs))
         .collect::<Result<Vec<_>, _>>()?;
 
+    // the type of the validation context, which is passed to all invariants
+    let context_type = options.context();
+    check_invariant_bindings(
+        fields,
+        struct_validate_attribute.as_ref(),
+        &field_validate_attributes,
+        context_type.is_some(),
+    )?;
+
     // these are the generics for the original type and the internal state
     // This is not the same as for the builder, since the builder has one additional
     // const-generic parameter which is the index of the last initialized field.
@@ -58,31 +104,42 @@ pub fn make_builder(input: &StructDeriveInput) -> Result<Builder, CompileError>
     let (original_impl_generics, original_ty_generics, original_where_clause) =
         input.generics.split_for_impl();
 
-    // this is like the impl generics but without the enclosing <...>
-    let struct_generics = &input.generics.params;
+    // this is like the impl generics but without the enclosing <...>. The
+    // defaults of the parameters are left out, see without_defaults.
+    let generics_without_defaults = without_defaults(&input.generics);
+    let struct_generics = &generics_without_defaults.params;
 
     // @todo make this visibility configurable
     let builder_vis = syn::token::Pub::default();
+    // the builder is declared next to the struct and it is visible wherever
+    // the struct is visible. It must not be visible beyond that, because the
+    // trait implementations on the builder mention the struct.
+    let builder_struct_vis = input.vis.to_token_stream();
 
     let maybe_trailing_comma: Option<syn::token::Comma> = if input.generics.params.is_empty() {
         None
     } else {
         Some(syn::token::Comma::default())
     };
+    // helper function to generate the builder type with the given type
+    // for the state, e.g. FooBuilder<'a,T1,T2,State>
+    let builder_type_with_state = |state: proc_macro2::TokenStream| {
+        let type_generics_without_angle_brackets =
+            TypeGenericsWithoutAngleBrackets::from(&input.generics);
+        quote! {#builder_ident <#type_generics_without_angle_brackets #maybe_trailing_comma #state>}
+    };
+
     // helper function to generate the builder type with a given count of
     // initialized fields, e.g FooBuilder<'a,T1,T2,()>
     let builder_type_with_count = |count: usize| {
-        let type_generics_without_angle_brackets =
-            TypeGenericsWithoutAngleBrackets::from(&input.generics);
-        let generic_tuple_types = fields.iter().take(count).map(|f| &f.ty);
-        quote! {#builder_ident <#type_generics_without_angle_brackets #maybe_trailing_comma ( #(#generic_tuple_types,)* )>}
+        let generic_tuple_types = set_fields.iter().take(count).map(|f| &f.ty);
+        builder_type_with_state(quote! {( #(#generic_tuple_types,)* )})
     };
 
     let initial_builder_type = builder_type_with_count(0);
-    // comma separated list of all field types
-    let all_field_types = fields.iter().map(|f| &f.ty);
 
     let builder_state_generic = format_ident!("__{}_State", builder_ident);
+    let builder_docs = builder_docs(original_struct_ident, set_fields);
     // this is for defining the builder struct,
     // implementing a constructor on it
     // and defining the Builder method on the original struct
@@ -93,18 +150,23 @@ pub fn make_builder(input: &StructDeriveInput) -> Result<Builder, CompileError>
         // initialized. Initialized happens top to bottom in order of declaration.
         // Thus, the builder starts at count 0, which indicates no
         // fields have been initialized.
+        // The phantom data is the original struct, so the builder has the
+        // same variance and drop check behavior as the struct. It is Send and
+        // Sync exactly if the struct is, see auto_trait_impls below.
+        #[doc = #builder_docs]
         #[allow(non_camel_case_types)]
         #[must_use]
-        pub struct #builder_ident <#struct_generics #maybe_trailing_comma #builder_state_generic> #original_where_clause{
-            state: #builder_state_generic,
-            phantom: ::core::marker::PhantomData<( #(#all_field_types),* )>,
+        #builder_struct_vis struct #builder_ident <#struct_generics #maybe_trailing_comma #builder_state_generic> #original_where_clause{
+            state: #runtime_crate::__private::State<#builder_state_generic>,
+            phantom: ::core::marker::PhantomData<#original_struct_ident #original_ty_generics>,
         }
 
         impl #original_impl_generics #initial_builder_type #original_where_clause {
-            pub fn new() -> Self {
+            /// create a builder where no fields are set yet.
+            pub const fn new() -> Self {
                 Self {
-                    state: Default::default(),
-                    phantom: Default::default(),
+                    state: #runtime_crate::__private::State::new(()),
+                    phantom: ::core::marker::PhantomData,
                 }
             }
         }
@@ -115,63 +177,155 @@ pub fn make_builder(input: &StructDeriveInput) -> Result<Builder, CompileError>
         impl #original_impl_generics #original_struct_ident #original_ty_generics
             #original_where_clause {
                 //@todo make this visibility configurable
-                #builder_vis fn builder() -> #builder_mod_ident :: #initial_builder_type {
-                    #builder_mod_ident::#builder_ident::new()
+                #[doc = #builder_docs]
+                #builder_vis const fn builder() -> #initial_builder_type {
+                    <#initial_builder_type>::new()
                 }
         }
     };
 
+    // with a context, the build function is called build_with and takes the
+    // context as an argument, which is then passed on to the invariants.
+    let context_ident = hygienic_ident("__validation_context");
+    let (build_fn_ident, build_arguments) = match context_type {
+        Some(context_type) => (
+            format_ident!("build_with"),
+            quote! {#context_ident: &#context_type,},
+        ),
+        None => (format_ident!("build"), quote! {}),
+    };
+
+    // the names of all setters, so that the additional functions on the
+    // builder don't clash with them
+    let setter_names = set_fields
+        .iter()
+        .map(|f| f.ident.to_string())
+        .collect::<std::collections::HashSet<_>>();
+
     // now we construct the chain of setter function on the builder, where
     // we go from count i to count i+1 by setting the field at
     // index i (starting with index 0, in order of declaration).
     // The generic tuple argument goes from () -> (TypeOfField0,) -> (TypeOfField0,TypeOfField1) ->...
     // That means that we transitively know that if the field at index i is set,
     // all fields at indices 0,...,i have been set.
-    let setters = fields.iter().enumerate().map(|(count, field)| {
+    let state_ident = hygienic_ident("__state");
+    let setters = set_fields.iter().enumerate().map(|(count, field)| {
         let previous_builder_type = builder_type_with_count(count);
         let next_builder_type = builder_type_with_count(count + 1);
-        let setter_fn = field.ident.as_ref().map(|ident| format_ident!("{}", ident));
-        let field_ident = &field.ident;
+        let setter_fn = &field.ident;
+        let field_ident = &local_ident(&field.ident);
         let field_type = &field.ty;
         let indices = (0..count).map(Index::from);
+        // the setter consumes the previous state, so it can only be const if the
+        // previous state does not need to be dropped.
+        // We also can't call the transform function in const contexts.
+        let transform = set_field_transforms[count];
+        let const_qualifier = (transform.is_none()
+            && set_fields
+                .iter()
+                .take(count)
+                .all(|f| has_no_drop_glue(&f.ty)))
+        .then(syn::token::Const::default);
+
+        let transform_logic = transform.map(|transform| make_transform_logic(field_ident, transform));
+        let setter_docs = setter_docs(set_fields, count, &build_fn_ident);
+
+        // the conditional setter picks one of two values, so that
+        // conditional configuration can stay in one chain. We don't generate
+        // it if that would clash with the setter of another field.
+        let conditional_setter_fn = format_ident!("{}_if", setter_fn);
+        // the value that is not picked is dropped, so it must not have drop
+        // glue for the function to be const.
+        let conditional_const_qualifier = const_qualifier.filter(|_| has_no_drop_glue(field_type));
+        let conditional_setter = (!setter_names.contains(&conditional_setter_fn.to_string())).then(|| {
+            quote! {
+                /// set the field to the first value if the condition is true
+                /// and to the second value otherwise.
+                #[must_use]
+                pub #conditional_const_qualifier fn #conditional_setter_fn (self, condition: bool, if_true: #field_type, if_false: #field_type) -> #next_builder_type {
+                    if condition {
+                        self.#setter_fn(if_true)
+                    } else {
+                        self.#setter_fn(if_false)
+                    }
+                }
+            }
+        });
 
         let setter_tokens = quote! {
 
          impl #original_impl_generics #previous_builder_type #original_where_clause {
+            #setter_docs
             #[must_use]
-            pub fn #setter_fn (self, #field_ident : #field_type) -> #next_builder_type {
-                let mut state = self.state;
+            pub #const_qualifier fn #setter_fn (self, #field_ident : #field_type) -> #next_builder_type {
+                #transform_logic
+                let #state_ident = self.state.into_inner();
                 #builder_ident {
-                    state : (#( state. #indices,)* #field_ident,),
-                    phantom: Default::default(),
+                    state : #runtime_crate::__private::State::new((#( #state_ident. #indices,)* #field_ident,)),
+                    phantom: ::core::marker::PhantomData,
                 }
             }
+
+            #conditional_setter
          }
 
         };
         setter_tokens
     });
 
+    // the apply function exists in every state of the builder and passes the
+    // builder to the given function. Again, we don't generate it if that would
+    // clash with the setter of a field.
+    let apply_tokens = (!setter_names.contains("apply")).then(|| {
+        let builder_type = builder_type_with_state(quote! {#builder_state_generic});
+        quote! {
+            #[allow(non_camel_case_types)]
+            impl <#struct_generics #maybe_trailing_comma #builder_state_generic> #builder_type #original_where_clause {
+                /// pass the builder to the given function and return its result,
+                /// which allows setting fields conditionally inside a chain.
+                pub fn apply<__R>(self, func: impl ::core::ops::FnOnce(Self) -> __R) -> __R {
+                    func(self)
+                }
+            }
+        }
+    });
+
     // this is to generate the build method on the final form of the builder where we
     // know that all fields have been initialized.
     // Also if we have no validate-attributes either on the struct itself or
     // on any of the fields, we return the type `Foo` from `FooBuilder`,
     // otherwise we return an `Option<Foo>` that fails if either of the
     // validate expressions fails.
-    let final_builder = builder_type_with_count(fields.len());
+    let final_builder = builder_type_with_count(set_fields.len());
 
     // helper expression that produces an instance of the structure that we
-    // are building from the finished builder state
+    // are building from the finished builder state. The set fields are bound
+    // to local variables of the same name, so that the expressions for the
+    // skipped fields can refer to them. The skipped fields are computed in
+    // order of declaration and can refer to the skipped fields before them.
     let finished_struct_expression = {
-        let field_names = fields
-            .iter()
-            .map(|f| f.ident.as_ref().expect("struct fields must be named"));
-        // let field_names_again = field_names.clone();
-        let indices = (0..field_names.len()).map(Index::from);
+        let local_ident = |field: &NamedField| local_ident(&field.ident);
+        let set_field_names = set_fields.iter().map(local_ident);
+        let skipped_fields =
+            fields
+                .iter()
+                .zip(field_options.iter())
+                .filter_map(|(field, options)| {
+                    let skip_expression = options.skip()?;
+                    let local_ident = local_ident(field);
+                    let field_type = &field.ty;
+                    Some(quote! {
+                        let #local_ident : #field_type = #skip_expression;
+                    })
+                });
+        let field_names = fields.iter().map(|f| &f.ident);
+        let local_names = fields.iter().map(local_ident);
         quote! {
             {
+                let (#(#set_field_names,)*) = self.state.into_inner();
+                #(#skipped_fields)*
                 #original_struct_ident {
-                    #(#field_names : self.state. #indices),*
+                    #(#field_names: #local_names),*
                 }
             }
         }
@@ -179,20 +333,117 @@ pub fn make_builder(input: &StructDeriveInput) -> Result<Builder, CompileError>
 
     // the identifier we use for the instance of the finished struct inside the builder
     // before validation and passing it outside
-    let finished_ident = format_ident!("{}", FINISHED_VALUE_IDENT);
+    let finished_ident = hygienic_ident(FINISHED_VALUE_IDENT);
 
     let has_validators = struct_validate_attribute.is_some()
         || field_validate_attributes.iter().any(|val| val.is_some());
+
+    if let (Some(context_type), false) = (context_type, has_validators) {
+        return Err(CompileError::new_spanned(
+            context_type,
+            "a validation context can only be used if there are invariants",
+        ));
+    }
+
+    // if any of the invariants is async, the build functions are async as well
+    let has_async_validators = struct_validate_attribute
+        .iter()
+        .chain(field_validate_attributes.iter().flatten())
+        .any(InvariantAttribute::is_async);
+    // the generated values are built synchronously
+    if let (Some(generator), true) = (options.generator(), has_async_validators) {
+        return Err(CompileError::new_spanned(
+            generator,
+            format!("`{generator}` can't be used together with async invariants"),
+        ));
+    }
+    let async_qualifier = has_async_validators.then(syn::token::Async::default);
+
+    let context_argument = context_type.map(|_| quote! {#context_ident});
+
+    // if we have validators, the build function returns an Option, otherwise
+    // it returns the struct itself.
+    let build_return_type = if has_validators {
+        quote! {::core::option::Option<#original_struct_ident #original_ty_generics>}
+    } else {
+        quote! {#original_struct_ident #original_ty_generics}
+    };
+
+    // the build function consumes the final state, so it can only be const if
+    // that does not have to be dropped. See also the comment on the setters.
+    // We can't know whether the expressions for skipped fields can be
+    // evaluated at compile time, so skipping a field makes the build function
+    // non-const, except for the PhantomData fields we skip ourselves. The
+    // same goes for the normalize function.
+    let can_build_be_const = field_options
+        .iter()
+        .all(|options| options.skip().is_none() || options.skips_phantom_data())
+        && options.normalize().is_none()
+        && fields.iter().all(|f| has_no_drop_glue(&f.ty));
+
+    // the normalize function is called on the finished instance before the
+    // invariants are checked
+    let normalize_logic = options.normalize().map(|normalize| {
+        let normalize_helper = hygienic_ident("__normalize");
+        quote! {
+            // this function helps us with making sure the argument
+            // of the closure gets deduced correctly
+            #[inline(always)]
+            fn #normalize_helper<__TType, __FType>(val: &mut __TType, func: __FType)
+            where __FType: ::core::ops::FnOnce(&mut __TType) {
+                (func)(val)
+            }
+            #normalize_helper(&mut #finished_ident, #normalize);
+        }
+    });
+    let mut_qualifier = options.normalize().map(|_| syn::token::Mut::default());
+
+    let original_struct_type = quote! {#original_struct_ident #original_ty_generics};
+    let finish_const_qualifier = can_build_be_const.then(syn::token::Const::default);
+
+    // this produces the finished and normalized instance from the final
+    // builder, without checking any invariants. It is used by all the
+    // build functions below.
+    let finish_tokens = quote! {
+         impl #original_impl_generics #final_builder #original_where_clause {
+            #[inline(always)]
+            #finish_const_qualifier fn __finish(self) -> #original_struct_type {
+                // Safety: this is safe because we know all fields have been
+                // initialized at this point.
+                let #mut_qualifier #finished_ident = #finished_struct_expression;
+                #normalize_logic
+                #finished_ident
+            }
+         }
+    };
+
+    // the invariants together with what they apply to, for the docs
+    let documented_invariants = fields
+        .iter()
+        .zip(field_validate_attributes.iter())
+        .filter_map(|(field, validator)| {
+            Some((format!("field `{}`", field.ident), validator.as_ref()?))
+        })
+        .chain(
+            struct_validate_attribute
+                .as_ref()
+                .map(|validator| (format!("`{original_struct_ident}`"), validator)),
+        )
+        .collect::<Vec<_>>();
+    let build_docs = build_docs(
+        original_struct_ident,
+        &documented_invariants,
+        context_type.is_some(),
+    );
+
     let builder_tokens = if !has_validators {
         // this is the simple case: if no validation is performed, we just return
         // the struct itself
         quote! {
              impl #original_impl_generics #final_builder #original_where_clause {
-                pub fn build(self) -> #original_struct_ident #original_ty_generics {
-                    // Safety: this is safe because we know all fields have been
-                    // initialized at this point.
-                    let #finished_ident = #finished_struct_expression;
-                    #finished_ident
+                #[doc = #build_docs]
+                pub #finish_const_qualifier fn build(self) -> #build_return_type {
+                    self.__finish()
                 }
              }
         }
@@ -200,103 +451,618 @@ pub fn make_builder(input: &StructDeriveInput) -> Result<Builder, CompileError>
         // in case we have validators, we return an Optional that only contains
         // the value if all validators pass successfully.
 
-        // the validator logic to be pasted inside the build function
-        let field_validator_logic = fields
-            .iter()
-            .zip(field_validate_attributes.iter())
-            .flat_map(|(field, maybe_validator)| {
-                let Some(validator) = maybe_validator else {
-                    return None;
-                };
-                let field_ident = field
-                    .ident
-                    .as_ref()
-                    .expect("named fields must have identifiers");
-                // let validator_binding = format_ident!("{}_validator", field_ident);
+        // if all the invariants are flagged as const, we can make the build
+        // function const as well. In this case we have to call the functions
+        // directly, because the __is_valid helper below can't be const.
+        // Note that the finished instance is dropped if the invariants are
+        // violated, so the fields must not have drop glue either.
+        let is_const_build = can_build_be_const
+            && struct_validate_attribute
+                .iter()
+                .chain(field_validate_attributes.iter().flatten())
+                .all(InvariantAttribute::is_const);
 
-                let validator_expression = validator.expression();
+        // the invariants flagged as debug_only are only checked with
+        // debug assertions enabled. The return type of the build function
+        // stays the same regardless, so that it does not depend on the profile.
+        // We use cfg! rather than #[cfg] so that the check is still type
+        // checked and the invariant is still used in release builds.
+        let debug_only_guard = |validator: &InvariantAttribute| {
+            validator
+                .is_debug_only()
+                .then(|| quote! {if ::core::cfg!(debug_assertions)})
+        };
 
-                let span = validator.expression_span();
-                // this is & for all types except references and pointers which
-                // are directly passed to the validators. All other types are
-                // passed as references.
-                let ref_qualifier = match field.ty {
-                    syn::Type::Ptr(_) => None,
-                    syn::Type::Reference(_) => None,
-                    _ => Some(syn::token::And {
-                        spans: [Span::call_site()],
-                    }),
-                };
+        let const_qualifier = is_const_build.then(syn::token::Const::default);
+
+        // this is & for all types except references and pointers which
+        // are directly passed to the validators. All other types are
+        // passed as references. See also check_argument_type.
+        let field_argument = |field: &NamedField| {
+            let field_ident = &field.ident;
+            let ref_qualifier = match field.ty {
+                syn::Type::Ptr(_) => None,
+                syn::Type::Reference(_) => None,
+                _ => Some(syn::token::And {
+                    spans: [Span::call_site()],
+                }),
+            };
+            quote! {#ref_qualifier #finished_ident . #field_ident}
+        };
 
-                Some(quote_spanned! {span=>
+        // the helper function that makes sure the arguments of the closures
+        // get deduced correctly. Every check function gets its own helper
+        // with the number of arguments it needs. We can't use it in const
+        // functions, so there we call the invariants directly.
+        let is_valid_ident = hygienic_ident("__is_valid");
+        let is_validated_ident = hygienic_ident("__is_validated");
+        let description_ident = hygienic_ident("__description");
+        let is_valid_helper = |arity: usize| {
+            if is_const_build {
+                return None;
+            }
+            let types = (0..arity)
+                .map(|index| format_ident!("__TType{}", index))
+                .collect::<Vec<_>>();
+            let values = (0..arity)
+                .map(|index| format_ident!("val{}", index))
+                .collect::<Vec<_>>();
+            Some(quote! {
+                 #[inline(always)]
+                 fn #is_valid_ident<#(#types:?Sized,)* __FType>(#(#values: &#types,)* func: __FType) -> bool
+                 where for<'__life> __FType: ::core::ops::FnOnce(#(&#types),*) -> bool {
+                     (func)(#(#values),*)
+                 }
+            })
+        };
+
+        // helper to create the statements that check the validator against the
+        // given arguments and return the description of the invariant if it
+        // is violated.
+        let check_logic =
+            |arguments: Vec<TokenStream>, validator: &InvariantAttribute, description: String| {
+                let validator_expression = validator.expression();
+                let validator_call = if is_const_build {
+                    quote! {#validator_expression(#(#arguments),*)}
+                } else {
+                    quote! {#is_valid_ident(#(#arguments,)* #validator_expression)}
+                };
+                let debug_only_guard = debug_only_guard(validator);
+                let span = validator.expression_span();
+                quote_spanned! {span=>
+                    #debug_only_guard
+                    {
+                        let #is_validated_ident : bool = #validator_call;
+                        if !#is_validated_ident {
+                            return ::core::result::Result::Err(#description);
+                        }
+                    }
+                }
+            };
 
-                    // this is a trick to make sure the correct type gets
-                    // deduced on the closures
-                    let is_validated : bool = __is_valid(#ref_qualifier #finished_ident . #field_ident,#validator_expression);
-                    // let is_validated = Self::__is_valid(#ref_qualifier #finished_ident . # field_ident,validator); 
-                    if !is_validated {
-                        return None;
+        // each field invariant gets its own check function, which takes the
+        // field and the fields it binds to (and the context) as arguments.
+        // Those are used for checking the finished instance, but also by the
+        // dynamic builder for checking single fields when they are set.
+        let field_check_fns = fields
+            .iter()
+            .zip(field_validate_attributes.iter())
+            .filter_map(|(field, maybe_validator)| {
+                let validator = maybe_validator.as_ref().filter(|v| !v.is_async())?;
+                let bound_fields = validator
+                    .bindings(context_type.is_some())
+                    .into_iter()
+                    .filter_map(|binding| fields.iter().find(|f| f.ident == *binding));
+                let parameters = std::iter::once(field).chain(bound_fields).collect::<Vec<_>>();
+                let parameter_idents = parameters
+                    .iter()
+                    .map(|f| local_ident(&f.ident))
+                    .collect::<Vec<_>>();
+                let parameter_types = parameters.iter().map(|f| check_argument_type(&f.ty));
+                let arguments = parameter_idents
+                    .iter()
+                    .map(|ident| quote! {#ident})
+                    .chain(context_argument.clone())
+                    .collect::<Vec<_>>();
+                let is_valid_helper = is_valid_helper(arguments.len());
+                let description = validator.describe(&format!(
+                    "field `{}` of `{original_struct_ident}`",
+                    field.ident
+                ));
+                let check_logic = check_logic(arguments, validator, description);
+                let check_fn_ident = field_check_fn_ident(field);
+                Some(quote! {
+                    #[allow(unused_variables)]
+                    #const_qualifier fn #check_fn_ident(#(#parameter_idents: #parameter_types,)* #build_arguments) -> ::core::result::Result<(), &'static str> {
+                        #is_valid_helper
+                        #check_logic
+                        ::core::result::Result::Ok(())
                     }
+                })
+            })
+            .collect::<Vec<_>>();
 
+        // the calls of the field check functions inside the function that
+        // checks the finished instance
+        let field_check_calls = fields
+            .iter()
+            .zip(field_validate_attributes.iter())
+            .filter_map(|(field, maybe_validator)| {
+                let validator = maybe_validator.as_ref().filter(|v| !v.is_async())?;
+                let bound_fields = validator
+                    .bindings(context_type.is_some())
+                    .into_iter()
+                    .filter_map(|binding| fields.iter().find(|f| f.ident == *binding));
+                let arguments = std::iter::once(field)
+                    .chain(bound_fields)
+                    .map(field_argument)
+                    .chain(context_argument.clone());
+                let check_fn_ident = field_check_fn_ident(field);
+                Some(quote! {
+                    if let ::core::result::Result::Err(#description_ident) = Self::#check_fn_ident(#(#arguments,)*) {
+                        return ::core::result::Result::Err(#description_ident);
+                    }
                 })
             });
 
-        let struct_validator_logic = struct_validate_attribute.map(|validator| {
-            let validator_expression = validator.expression();
-            let span = validator_expression.span();
+        let struct_check_logic = struct_validate_attribute
+            .as_ref()
+            .filter(|v| !v.is_async())
+            .map(|validator| {
+                let arguments = std::iter::once(quote! {#finished_ident})
+                    .chain(context_argument.clone())
+                    .collect::<Vec<_>>();
+                let is_valid_helper = is_valid_helper(arguments.len());
+                let description = validator.describe(&format!("`{original_struct_ident}`"));
+                let check_logic = check_logic(arguments, validator, description);
+                quote! {
+                    #is_valid_helper
+                    #check_logic
+                }
+            });
 
+        // the async invariants are awaited one after the other, after all
+        // the synchronous invariants hold. Those are always functions, so
+        // there is no need for the __is_valid helper.
+        let async_check_logic = |arguments: Vec<TokenStream>,
+                                 validator: &InvariantAttribute,
+                                 description: String| {
+            let validator_expression = validator.expression();
+            let debug_only_guard = debug_only_guard(validator);
+            let span = validator.expression_span();
             quote_spanned! {span=>
-                let is_validated : bool = __is_valid(& #finished_ident, #validator_expression);
-                if !is_validated {
-                    return None;
+                #debug_only_guard
+                {
+                    let #is_validated_ident : bool = #validator_expression(#(#arguments),*).await;
+                    if !#is_validated_ident {
+                        return ::core::result::Result::Err(#description);
+                    }
+                }
+            }
+        };
+        let async_field_checks = fields
+            .iter()
+            .zip(field_validate_attributes.iter())
+            .filter_map(|(field, maybe_validator)| {
+                let validator = maybe_validator.as_ref().filter(|v| v.is_async())?;
+                let bound_fields = validator
+                    .bindings(context_type.is_some())
+                    .into_iter()
+                    .filter_map(|binding| fields.iter().find(|f| f.ident == *binding));
+                let arguments = std::iter::once(field)
+                    .chain(bound_fields)
+                    .map(field_argument)
+                    .chain(context_argument.clone())
+                    .collect();
+                let description = validator.describe(&format!(
+                    "field `{}` of `{original_struct_ident}`",
+                    field.ident
+                ));
+                Some(async_check_logic(arguments, validator, description))
+            });
+        let async_struct_check = struct_validate_attribute
+            .as_ref()
+            .filter(|v| v.is_async())
+            .map(|validator| {
+                let arguments = std::iter::once(quote! {#finished_ident})
+                    .chain(context_argument.clone())
+                    .collect();
+                let description = validator.describe(&format!("`{original_struct_ident}`"));
+                async_check_logic(arguments, validator, description)
+            });
+        let async_check_fn = has_async_validators.then(|| {
+            quote! {
+                /// check the async invariants on the finished instance and return
+                /// a description of the first invariant that is violated, if any.
+                async fn __check_async_invariants(#finished_ident: &#original_struct_type, #build_arguments) -> ::core::result::Result<(), &'static str> {
+                    #(#async_field_checks)*
+
+                    #async_struct_check
+
+                    ::core::result::Result::Ok(())
                 }
             }
         });
 
+        // the result of checking all invariants, where the async ones are
+        // only checked if the others hold
+        let check_all_invariants = if has_async_validators {
+            quote! {
+                match Self::__check_invariants(&#finished_ident, #context_argument) {
+                    ::core::result::Result::Ok(()) => Self::__check_async_invariants(&#finished_ident, #context_argument).await,
+                    #description_ident => #description_ident,
+                }
+            }
+        } else {
+            quote! {Self::__check_invariants(&#finished_ident, #context_argument)}
+        };
+        // we can't track the caller through async functions
+        let track_caller = (!has_async_validators).then(|| quote! {#[track_caller]});
+
+        let build_expect_fn_ident = format_ident!("{}_expect", build_fn_ident);
+        let msg_ident = hygienic_ident("msg");
+
         quote! {
              impl #original_impl_generics #final_builder #original_where_clause {
-                 pub fn build(self) -> ::core::option::Option<#original_struct_ident #original_ty_generics> {
-                     // this function helps us with making sure the arguments
-                     // of the closures get deduced correctly
-                     // it is used above.
-                     #[inline(always)]
-                     fn __is_valid<__TType:?Sized,__FType>(val: &__TType, func: __FType) -> bool
-                     where for<'__life> __FType: FnOnce(&__TType) -> bool {
-                         (func)(val)
-                     }
-                     // Safety: this is safe because we know all fields have been
-                     // initialized at this point.
-                     // finished structure, this still has to undergo validation
-                     let #finished_ident = #finished_struct_expression;
+                 /// check the invariants on the finished instance and return a
+                 /// description of the first invariant that is violated, if any.
+                 /// The async invariants are checked separately.
+                 #[allow(unused_variables)]
+                 #const_qualifier fn __check_invariants(#finished_ident: &#original_struct_type, #build_arguments) -> ::core::result::Result<(), &'static str> {
+                     #(#field_check_calls)*
 
-                     #(#field_validator_logic)*
+                     #struct_check_logic
 
-                     #struct_validator_logic
+                     ::core::result::Result::Ok(())
+                 }
+
+                 #(#field_check_fns)*
+
+                 #async_check_fn
 
-                     Some(#finished_ident)
+                 #[doc = #build_docs]
+                 pub #const_qualifier #async_qualifier fn #build_fn_ident(self, #build_arguments) -> #build_return_type {
+                     // finished structure, this still has to undergo validation
+                     let #finished_ident = self.__finish();
+                     match #check_all_invariants {
+                         ::core::result::Result::Ok(()) => ::core::option::Option::Some(#finished_ident),
+                         ::core::result::Result::Err(_) => ::core::option::Option::None,
+                     }
+                 }
+
+                 /// build the instance and panic if any of the invariants are
+                 /// violated. The panic message is the given message followed by
+                 /// a description of the violated invariant.
+                 #track_caller
+                 pub #async_qualifier fn #build_expect_fn_ident(self, #build_arguments #msg_ident: &str) -> #original_struct_type {
+                     let #finished_ident = self.__finish();
+                     if let ::core::result::Result::Err(#description_ident) = #check_all_invariants {
+                         ::core::panic!("{}: {}", #msg_ident, #description_ident);
+                     }
+                     #finished_ident
+                 }
+
+                 /// build the instance without checking any of the invariants.
+                 ///
+                 /// # Safety
+                 ///
+                 /// The caller must make sure that all invariants hold. Code that
+                 /// relies on the invariants, including unsafe code, may misbehave
+                 /// otherwise.
+                 pub #finish_const_qualifier unsafe fn build_unchecked(self) -> #original_struct_type {
+                     self.__finish()
                  }
              }
         }
     };
 
+    // the names of the actual methods of the builder in each state, which
+    // the hidden methods below must not clash with
+    let method_names_with_count = |count: usize| {
+        let mut names = Vec::new();
+        if count == 0 {
+            names.push("new".to_string());
+        }
+        match set_fields.get(count) {
+            Some(field) => {
+                names.push(field.ident.to_string());
+                let conditional_setter = format!("{}_if", field.ident);
+                if !setter_names.contains(&conditional_setter) {
+                    names.push(conditional_setter);
+                }
+            }
+            None => {
+                names.push(build_fn_ident.to_string());
+                if has_validators {
+                    names.push(format!("{build_fn_ident}_expect"));
+                    names.push("build_unchecked".to_string());
+                }
+            }
+        }
+        names
+    };
+
+    // hidden methods that give helpful error messages when the builder
+    // is used in the wrong order
+    let misuse_diagnostics = make_misuse_diagnostics(
+        original_struct_ident,
+        set_fields,
+        &original_impl_generics,
+        original_where_clause,
+        builder_type_with_count,
+        method_names_with_count,
+        BuildSignature {
+            ident: &build_fn_ident,
+            arguments: &build_arguments,
+            return_type: &build_return_type,
+        },
+    );
+
+    // the traits given in #[quick_builder(derive(...))]
+    // the dynamic builder can check the invariants of the fields when they are
+    // set, if the invariants only depend on the field itself
+    let set_field_check_fns = fields
+        .iter()
+        .zip(field_options.iter())
+        .zip(field_validate_attributes.iter())
+        .filter(|((_, options), _)| options.skip().is_none())
+        .map(|((field, _), validator)| {
+            let validator = validator.as_ref().filter(|v| !v.is_async())?;
+            (context_type.is_none() && validator.bindings(false).is_empty())
+                .then(|| field_check_fn_ident(field))
+        })
+        .collect::<Vec<_>>();
+
+    // whether set_str can set the fields, unless they opted out
+    let set_field_parses_str = field_options
+        .iter()
+        .filter(|options| options.skip().is_none())
+        .map(|options| !options.no_set_str())
+        .collect::<Vec<_>>();
+
+    // the dynamic builder, if requested with #[quick_builder(dyn_builder)]
+    let dyn_builder = options.dyn_builder().then(|| {
+        make_dyn_builder(DynBuilderInput {
+            original_struct_ident,
+            builder_ident: &builder_ident,
+            vis: &builder_struct_vis,
+            generics: &input.generics,
+            set_fields,
+            set_field_transforms: &set_field_transforms,
+            final_builder: &final_builder,
+            has_validators,
+            has_async_validators,
+            context_type,
+            runtime_crate: &runtime_crate,
+            set_str: options.set_str(),
+            set_field_check_fns: &set_field_check_fns,
+            set_field_parses_str: &set_field_parses_str,
+        })
+    });
+
+    // the implementations of proptest's and arbitrary's Arbitrary, if requested
+    let set_field_strategies = field_options
+        .iter()
+        .filter(|options| options.skip().is_none())
+        .map(FieldOptions::strategy)
+        .collect::<Vec<_>>();
+    let generator_input = GeneratorInput {
+        original_struct_ident,
+        generics: &input.generics,
+        set_fields,
+        set_field_strategies: &set_field_strategies,
+        has_validators,
+        runtime_crate: &runtime_crate,
+    };
+    let proptest_arbitrary = options
+        .proptest()
+        .map(|proptest_option| make_proptest_arbitrary(proptest_option, &generator_input));
+    let arbitrary = options
+        .arbitrary()
+        .map(|arbitrary_option| make_arbitrary(arbitrary_option, &generator_input));
+
+    let builder_derives = make_builder_derives(
+        &options,
+        &builder_ident,
+        &input.generics,
+        set_fields,
+        builder_type_with_state,
+        builder_type_with_count,
+    );
+
+    // the trait that gives generic code access to the builder
+    let buildable_impl = quote! {
+        impl #original_impl_generics #runtime_crate::Buildable for #original_struct_ident #original_ty_generics
+            #original_where_clause {
+                type Builder = #initial_builder_type;
+                type Output = #build_return_type;
+
+                fn builder() -> Self::Builder {
+                    <Self::Builder>::new()
+                }
+        }
+    };
+
+    // the aliases for the stages of the builder, e.g. FooBuilderAfterWidth
+    let stage_aliases = options
+        .stage_aliases()
+        .map(|_| {
+            make_stage_aliases(
+                original_struct_ident,
+                &builder_ident,
+                &builder_struct_vis,
+                &input.generics,
+                set_fields,
+                builder_type_with_count,
+            )
+        })
+        .transpose()?;
+
+    // the field markers and the implementations of the traits of the runtime
+    // crate for the stages. Building with a context needs the context and
+    // building with async invariants gives a future, so those builders can't
+    // implement IsComplete.
+    let (fields_module, stage_traits) = if options.stage_traits() {
+        (
+            Some(make_fields_module(
+                original_struct_ident,
+                &builder_struct_vis,
+                set_fields,
+            )),
+            Some(make_stage_traits(
+                original_struct_ident,
+                set_fields,
+                &original_impl_generics,
+                original_where_clause,
+                &runtime_crate,
+                builder_type_with_count,
+                (context_type.is_none() && !has_async_validators).then_some(&build_return_type),
+            )),
+        )
+    } else {
+        (None, None)
+    };
+    // the builder has the same auto traits as the struct. Left to the compiler,
+    // they would also depend on the fields set so far, so that e.g. the builder
+    // of a struct that implements Send for its raw pointer field would not be
+    // Send once the pointer is set. We only implement them for the stages, so
+    // that there is no builder with any other state that is Send or Sync.
+    let auto_trait_impls = (0..=set_fields.len()).map(|count| {
+        let builder_type = builder_type_with_count(count);
+        let send_bound = higher_ranked_bound(
+            quote! {#original_struct_ident #original_ty_generics},
+            quote! {::core::marker::Send},
+        );
+        let sync_bound = higher_ranked_bound(
+            quote! {#original_struct_ident #original_ty_generics},
+            quote! {::core::marker::Sync},
+        );
+        let where_predicates = original_where_clause
+            .map(|clause| clause.predicates.iter().collect::<Vec<_>>())
+            .unwrap_or_default();
+        quote! {
+            // Safety: the state only holds values for the fields of the struct,
+            // which end up in the struct when it is built. So it is safe to
+            // send or share them if it is safe to send or share the struct.
+            unsafe impl #original_impl_generics ::core::marker::Send for #builder_type
+            where
+                #(#where_predicates,)*
+                #send_bound,
+            {}
+
+            unsafe impl #original_impl_generics ::core::marker::Sync for #builder_type
+            where
+                #(#where_predicates,)*
+                #sync_bound,
+            {}
+        }
+    });
+
     let tokens = quote! {
+        // the builder and the names that come with it are declared next to the
+        // struct, so that they can be named wherever the struct can be named.
+        // That also works for structs that are declared inside functions.
+        #builder_struct_tokens
+
+        #stage_aliases
+
+        #fields_module
 
-        // implement the Foo::builder() function which returns the initial FooBuilder
-        #impl_build_function_on_original_struct_tokens
+        #dyn_builder
 
-        // the actual FooBuilder data structures and logic are namespaced in a
-        // module so that no internal state can leak out
-        #[allow(non_snake_case)]
-        // #[doc(hidden)]
-        #builder_vis mod #builder_mod_ident {
-            use super::*;
-            #builder_struct_tokens
+        // everything else only consists of implementations and helpers that
+        // must not clash with anything else in the scope of the struct.
+        const _: () = {
+            // implement the Foo::builder() function which returns the initial FooBuilder
+            #impl_build_function_on_original_struct_tokens
+
+            #buildable_impl
+
+            #stage_traits
+
+            #(#auto_trait_impls)*
 
             #(#setters)*
 
+            #apply_tokens
+
+            #finish_tokens
+
             #builder_tokens
-        }
+
+            #misuse_diagnostics
+
+            #builder_derives
+
+            #proptest_arbitrary
+
+            #arbitrary
+        };
     };
 
     Ok(Builder { tokens })
 }
+
+/// the statements that apply the transform function to the value of the field
+/// inside a setter, where the value is bound to the identifier of the field.
+fn make_transform_logic(field_ident: &Ident, transform: &Expr) -> TokenStream {
+    let transform_helper = hygienic_ident("__transform");
+    quote! {
+        // this function helps us with making sure the argument
+        // of the closure gets deduced correctly
+        #[inline(always)]
+        fn #transform_helper<__TType, __FType>(val: __TType, func: __FType) -> __TType
+        where __FType: ::core::ops::FnOnce(__TType) -> __TType {
+            (func)(val)
+        }
+        let #field_ident = #transform_helper(#field_ident, #transform);
+    }
+}
+
+/// pass the items through the given macro of the runtime crate, which checks
+/// that the feature the items need is enabled. We can't see the features of
+/// the runtime crate from here, so the macro is a compile error naming the
+/// feature if it is disabled. The whole invocation gets the span of the option
+/// that requested the items, so that the error points at the option.
+fn requires_feature(
+    runtime_crate: &TokenStream,
+    guard: &str,
+    option: &Ident,
+    items: TokenStream,
+) -> TokenStream {
+    let span = option.span();
+    let runtime_crate = runtime_crate.clone().into_iter().map(|mut token| {
+        token.set_span(span);
+        token
+    });
+    let guard = Ident::new(guard, span);
+    quote_spanned! {span=>
+        #(#runtime_crate)*::__private::#guard! {
+            #items
+        }
+    }
+}
+
+/// the name of the function that checks the invariant of the given field
+fn field_check_fn_ident(field: &NamedField) -> Ident {
+    format_ident!("__check_{}", field.ident)
+}
+
+/// the type in which the field of the given type is passed to the invariant.
+/// This is a reference to the type, except for references and pointers which
+/// are passed directly. Mutable references are passed as shared references.
+/// Those are reborrowed from the finished instance, so they can't have the
+/// lifetime of the field and we leave it to elision instead.
+fn check_argument_type(ty: &Type) -> TokenStream {
+    match ty {
+        Type::Ptr(_) => quote! {#ty},
+        Type::Reference(reference) => {
+            let mut reference = reference.clone();
+            if reference.mutability.take().is_some() {
+                reference.lifetime = None;
+            }
+            quote! {#reference}
+        }
+        _ => quote! {&#ty},
+    }
+}
@@ -0,0 +1,63 @@
+//! helpers to decide whether the generated functions can be made `const fn`.
+//!
+//! Functions that consume the builder can only be `const` if the builder state
+//! does not have to be dropped, because on stable rust, the compiler does not
+//! allow running (or even potentially running) destructors in const functions.
+//! Since we only have the syntax of the types at macro expansion time, we
+//! have to conservatively guess which types have no drop glue.
+
+use syn::{GenericArgument, PathArguments, Type};
+
+use crate::detail::{is_phantom_data, is_std_path};
+
+/// the primitive types, which we know don't have any drop glue
+const PRIMITIVE_TYPES: &[&str] = &[
+    "bool", "char", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128",
+    "isize", "f32", "f64",
+];
+
+/// wrapper types from the standard library together with their module, which
+/// have no drop glue if all their generic type arguments don't have drop glue.
+const TRANSPARENT_WRAPPERS: &[(&str, &str)] = &[("option", "Option")];
+
+/// returns true if we know for sure that the given type has no drop glue.
+/// Returns false if the type (potentially) needs to be dropped, or if we
+/// can't tell from the syntax alone, e.g. for generic types or user defined
+/// types. Types are only recognized by their bare name or their full path in
+/// the standard library, because other paths may name user types that shadow
+/// them.
+pub fn has_no_drop_glue(ty: &Type) -> bool {
+    // PhantomData never has drop glue, whatever its type argument is
+    if is_phantom_data(ty) {
+        return true;
+    }
+    match ty {
+        Type::Reference(_) | Type::Ptr(_) | Type::BareFn(_) | Type::Never(_) => true,
+        Type::Array(array) => has_no_drop_glue(&array.elem),
+        Type::Tuple(tuple) => tuple.elems.iter().all(has_no_drop_glue),
+        Type::Paren(paren) => has_no_drop_glue(&paren.elem),
+        Type::Group(group) => has_no_drop_glue(&group.elem),
+        Type::Path(path) if path.qself.is_none() => {
+            let Some(last) = path.path.segments.last() else {
+                return false;
+            };
+            match &last.arguments {
+                PathArguments::None => PRIMITIVE_TYPES
+                    .iter()
+                    .any(|primitive| is_std_path(&path.path, "primitive", primitive)),
+                PathArguments::AngleBracketed(arguments) => {
+                    TRANSPARENT_WRAPPERS
+                        .iter()
+                        .any(|(module, wrapper)| is_std_path(&path.path, module, wrapper))
+                        && arguments.args.iter().all(|arg| match arg {
+                            GenericArgument::Type(ty) => has_no_drop_glue(ty),
+                            GenericArgument::Lifetime(_) => true,
+                            _ => false,
+                        })
+                }
+                PathArguments::Parenthesized(_) => false,
+            }
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,135 @@
+//! this module implements the traits on the builder that were requested with
+//! `#[quick_builder(derive(...))]`.
+//!
+//! We can't just forward the derives to the builder struct, because the std
+//! derives would put bounds on all generic parameters of the struct. However,
+//! the builder only contains the fields that have already been set, so it
+//! should e.g. be `Clone` if and only if the fields set so far are `Clone`.
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse_quote, Generics, Index};
+
+use crate::{
+    detail::{higher_ranked_bound, NamedField},
+    options::{BuilderDerive, BuilderOptions},
+};
+
+/// generate the trait implementations for all the derives in the options.
+///
+/// The `builder_type_with_state` closure must give the builder type with the
+/// given tokens as the type of the state and the `builder_type_with_count`
+/// closure must give the builder type where the given count of fields has
+/// been initialized.
+pub fn make_builder_derives(
+    options: &BuilderOptions,
+    builder_ident: &Ident,
+    generics: &Generics,
+    fields: &[NamedField],
+    builder_type_with_state: impl Fn(TokenStream) -> TokenStream,
+    builder_type_with_count: impl Fn(usize) -> TokenStream,
+) -> TokenStream {
+    let state = format_ident!("__{}_State", builder_ident);
+
+    // the impl generics and where clause for a trait implementation that is
+    // generic over the builder state, where the state must implement the
+    // given trait.
+    let generic_state_impl = |state_bound: TokenStream, body: TokenStream| {
+        let mut generics = generics.clone();
+        generics.params.push(parse_quote!(#state: #state_bound));
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let builder_type = builder_type_with_state(quote! {#state});
+        quote! {
+            #[allow(non_camel_case_types)]
+            impl #impl_generics #state_bound for #builder_type #where_clause {
+                #body
+            }
+        }
+    };
+
+    let derives = options.derives().map(|derive| match derive {
+        BuilderDerive::Clone => generic_state_impl(
+            quote! {::core::clone::Clone},
+            quote! {
+                fn clone(&self) -> Self {
+                    Self {
+                        state: ::core::clone::Clone::clone(&self.state),
+                        phantom: ::core::marker::PhantomData,
+                    }
+                }
+            },
+        ),
+        BuilderDerive::Copy => generic_state_impl(quote! {::core::marker::Copy}, quote! {}),
+        BuilderDerive::PartialEq => generic_state_impl(
+            quote! {::core::cmp::PartialEq},
+            quote! {
+                fn eq(&self, other: &Self) -> bool {
+                    ::core::cmp::PartialEq::eq(&self.state, &other.state)
+                }
+            },
+        ),
+        BuilderDerive::Eq => generic_state_impl(quote! {::core::cmp::Eq}, quote! {}),
+        BuilderDerive::Hash => generic_state_impl(
+            quote! {::core::hash::Hash},
+            quote! {
+                fn hash<__H: ::core::hash::Hasher>(&self, state: &mut __H) {
+                    ::core::hash::Hash::hash(&self.state, state)
+                }
+            },
+        ),
+        BuilderDerive::Debug => {
+            make_debug_impls(builder_ident, generics, fields, &builder_type_with_count)
+        }
+    });
+
+    quote! {
+        #(#derives)*
+    }
+}
+
+/// the debug implementation prints the fields that have been set by name,
+/// so we have to implement it for every state of the builder separately.
+fn make_debug_impls(
+    builder_ident: &Ident,
+    generics: &Generics,
+    fields: &[NamedField],
+    builder_type_with_count: impl Fn(usize) -> TokenStream,
+) -> TokenStream {
+    let builder_name = builder_ident.to_string();
+    let impls = (0..=fields.len()).map(|count| {
+        let set_fields = &fields[..count];
+        let mut generics = generics.clone();
+        let where_clause = generics.make_where_clause();
+        for field in set_fields {
+            let ty = &field.ty;
+            where_clause
+                .predicates
+                .push(higher_ranked_bound(ty, quote! {::core::fmt::Debug}));
+        }
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let builder_type = builder_type_with_count(count);
+
+        let field_names = set_fields.iter().map(|f| f.ident.to_string());
+        let indices = (0..count).map(Index::from);
+        // we indicate that there are fields left to set, just like a
+        // non-exhaustive struct
+        let finish = if count == fields.len() {
+            quote! {finish}
+        } else {
+            quote! {finish_non_exhaustive}
+        };
+
+        quote! {
+            impl #impl_generics ::core::fmt::Debug for #builder_type #where_clause {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    f.debug_struct(#builder_name)
+                        #(.field(#field_names, &self.state.get().#indices))*
+                        .#finish()
+                }
+            }
+        }
+    });
+
+    quote! {
+        #(#impls)*
+    }
+}
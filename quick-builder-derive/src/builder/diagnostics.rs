@@ -0,0 +1,176 @@
+//! this module generates the machinery for helpful error messages when the
+//! builder is used incorrectly, i.e. when `build()` is called before all fields
+//! are set or when a setter is called out of order.
+//!
+//! The setters and the build function only exist for the builder in the
+//! correct state, so calling them in the wrong state gives the generic
+//! "no method named `...` found" error. To improve on that, we generate hidden
+//! methods of the same name for all the other states. Those methods have
+//! bounds on traits that are never implemented and that carry a
+//! `#[diagnostic::on_unimplemented(...)]` message naming the offending field.
+//!
+//! The bounds like `FooBuilder<(i32,)>: __NotSet_second` are higher ranked,
+//! see `higher_ranked_bound`.
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{ImplGenerics, WhereClause};
+
+use crate::detail::{higher_ranked_bound, NamedField};
+
+/// the signature of the actual build function, which the hidden build
+/// functions must mirror.
+pub struct BuildSignature<'a> {
+    /// the name of the build function
+    pub ident: &'a Ident,
+    /// the arguments other than self, including a trailing comma
+    pub arguments: &'a TokenStream,
+    /// the return type
+    pub return_type: &'a TokenStream,
+}
+
+/// the name of the trait that signals that the given field has not been set.
+fn not_set_trait_ident(field: &NamedField) -> Ident {
+    format_ident!("__NotSet_{}", field.ident)
+}
+
+/// the name of the trait that signals that the given field has already been set.
+fn already_set_trait_ident(field: &NamedField) -> Ident {
+    format_ident!("__AlreadySet_{}", field.ident)
+}
+
+/// generate the diagnostic traits and the hidden methods on the builder in
+/// all of its states.
+///
+/// The `builder_type_with_count` closure must give the builder type where the
+/// given count of fields has been initialized, and `method_names_with_count`
+/// the names of the actual methods of that type. We leave out the hidden
+/// methods with those names, e.g. the hidden build function for a field
+/// called `build`, because they would clash with the actual methods.
+pub fn make_misuse_diagnostics(
+    original_struct_ident: &Ident,
+    fields: &[NamedField],
+    impl_generics: &ImplGenerics,
+    where_clause: Option<&WhereClause>,
+    builder_type_with_count: impl Fn(usize) -> TokenStream,
+    method_names_with_count: impl Fn(usize) -> Vec<String>,
+    build_signature: BuildSignature,
+) -> TokenStream {
+    let field_order = fields
+        .iter()
+        .map(|f| format!("`{}`", f.ident))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let order_note = format!(
+        "the fields of `{original_struct_ident}` must be set in order of declaration: {field_order}"
+    );
+
+    let diagnostic_traits = fields.iter().map(|field| {
+        let not_set_trait = not_set_trait_ident(field);
+        let already_set_trait = already_set_trait_ident(field);
+        let not_set_message = format!(
+            "field `{}` of `{original_struct_ident}` has not been set",
+            field.ident
+        );
+        let not_set_label = format!("field `{}` must be set before this", field.ident);
+        let already_set_message = format!(
+            "field `{}` of `{original_struct_ident}` has already been set",
+            field.ident
+        );
+        quote! {
+            #[diagnostic::on_unimplemented(
+                message = #not_set_message,
+                label = #not_set_label,
+                note = #order_note,
+            )]
+            #[allow(non_camel_case_types)]
+            #[doc(hidden)]
+            pub trait #not_set_trait {}
+
+            #[diagnostic::on_unimplemented(
+                message = #already_set_message,
+                label = "each field can only be set once",
+                note = #order_note,
+            )]
+            #[allow(non_camel_case_types)]
+            #[doc(hidden)]
+            pub trait #already_set_trait {}
+        }
+    });
+
+    // for each state of the builder, we generate the methods that are not
+    // allowed in this state.
+    let hidden_methods = (0..=fields.len()).map(|count| {
+        let builder_type = builder_type_with_count(count);
+        let method_names = method_names_with_count(count);
+        let is_free = |ident: &Ident| !method_names.contains(&ident.to_string());
+        // the first field that has not been set in this state, if any
+        let first_not_set_trait = fields.get(count).map(not_set_trait_ident);
+
+        let BuildSignature {
+            ident: build_fn_ident,
+            arguments: build_arguments,
+            return_type: build_return_type,
+        } = &build_signature;
+        let hidden_build = first_not_set_trait
+            .as_ref()
+            .filter(|_| is_free(build_fn_ident))
+            .map(|not_set_trait| {
+                let bound = higher_ranked_bound(quote! {Self}, not_set_trait);
+                quote! {
+                    #[doc(hidden)]
+                    pub fn #build_fn_ident(self, #build_arguments) -> #build_return_type
+                    where
+                        #bound,
+                    {
+                        ::core::unreachable!()
+                    }
+                }
+            });
+
+        // a field with the name of the build function can't have a hidden
+        // setter next to the hidden build function, so the latter wins.
+        let hidden_setters = fields
+            .iter()
+            .enumerate()
+            .filter(|(index, field)| *index != count && is_free(&field.ident))
+            .filter(|(_, field)| hidden_build.is_none() || field.ident != **build_fn_ident)
+            .map(|(index, field)| {
+                let setter_fn = &field.ident;
+                let field_type = &field.ty;
+                // the setters which are called too early return the builder
+                // type they would have returned if called in the correct order.
+                // This makes sure that there is only one error per wrong call
+                // in a chain of setters.
+                let (bound, return_type) = if index > count {
+                    (
+                        first_not_set_trait.clone(),
+                        builder_type_with_count(index + 1),
+                    )
+                } else {
+                    (Some(already_set_trait_ident(field)), quote! {Self})
+                };
+                let bound = higher_ranked_bound(quote! {Self}, bound);
+                quote! {
+                    #[doc(hidden)]
+                    pub fn #setter_fn(self, _: #field_type) -> #return_type
+                    where
+                        #bound,
+                    {
+                        ::core::unreachable!()
+                    }
+                }
+            });
+
+        quote! {
+            impl #impl_generics #builder_type #where_clause {
+                #hidden_build
+                #(#hidden_setters)*
+            }
+        }
+    });
+
+    quote! {
+        #(#diagnostic_traits)*
+        #(#hidden_methods)*
+    }
+}
@@ -0,0 +1,100 @@
+//! this module generates the documentation for the builder, so that rustdoc
+//! and IDE hovers have something useful to show for the generated items.
+//!
+//! The docs of the fields are forwarded to their setters and the rest is
+//! rendered from what we know about the struct: the order in which the fields
+//! must be set and the invariants that are checked when building.
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::Attribute;
+
+use crate::{detail::NamedField, validation::InvariantAttribute};
+
+/// the `///` doc comments among the given attributes
+fn doc_attributes(attributes: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attributes.iter().filter(|attr| attr.path().is_ident("doc"))
+}
+
+/// the fields as a comma separated list of names in backticks
+fn field_list(fields: &[NamedField]) -> String {
+    fields
+        .iter()
+        .map(|f| format!("`{}`", f.ident))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// the docs of the builder struct and of the functions that create it
+pub fn builder_docs(original_struct_ident: &Ident, set_fields: &[NamedField]) -> String {
+    format!(
+        "a builder for [`{original_struct_ident}`]. The fields must be set in this order: {}. \
+        Setting them out of order or building before all of them are set is a compile error.",
+        field_list(set_fields)
+    )
+}
+
+/// the docs of the setter for the field at the given index of the set fields.
+/// Those are the docs of the field itself, followed by a note on the
+/// order of the fields.
+pub fn setter_docs(set_fields: &[NamedField], index: usize, build_fn_ident: &Ident) -> TokenStream {
+    let field = &set_fields[index];
+    let field_docs = doc_attributes(&field.attrs);
+    let next = match set_fields.get(index + 1) {
+        Some(next) => format!("The next field to set is `{}`.", next.ident),
+        None => format!("This is the last field, call `{build_fn_ident}()` next."),
+    };
+    let order = format!("set the field `{}`. {next}", field.ident);
+    quote! {
+        #(#field_docs)*
+        #[doc = ""]
+        #[doc = #order]
+    }
+}
+
+/// the docs of the setters of the dynamic builder, which are the docs of the
+/// fields followed by a line that says which field is set. That way, the
+/// setter is documented even if the field is not.
+pub fn dyn_setter_docs(field: &NamedField) -> TokenStream {
+    let field_docs = doc_attributes(&field.attrs);
+    let summary = format!("set the field `{}`.", field.ident);
+    quote! {
+        #(#field_docs)*
+        #[doc = ""]
+        #[doc = #summary]
+    }
+}
+
+/// the docs of the build function. The invariants are given together with a
+/// description of what they apply to, e.g. "field `x`".
+pub fn build_docs(
+    original_struct_ident: &Ident,
+    invariants: &[(String, &InvariantAttribute)],
+    has_context: bool,
+) -> String {
+    if invariants.is_empty() {
+        return format!("build the [`{original_struct_ident}`] from the fields that were set.");
+    }
+    let mut docs = format!(
+        "build the [`{original_struct_ident}`] and check its invariants. \
+        Returns `None` if any of the following invariants are violated:\n\n"
+    );
+    for (subject, invariant) in invariants {
+        let debug_only = if invariant.is_debug_only() {
+            " (only checked in debug builds)"
+        } else {
+            ""
+        };
+        let is_async = if invariant.is_async() { " (async)" } else { "" };
+        docs.push_str(&format!(
+            "* {subject}: `{}`{is_async}{debug_only}\n",
+            invariant.source()
+        ));
+    }
+    if invariants.iter().any(|(_, invariant)| invariant.is_async()) {
+        docs.push_str("\nThe async invariants are only awaited once all other invariants hold.");
+    }
+    if has_context {
+        docs.push_str("\nThe validation context is passed to all invariants.");
+    }
+    docs
+}
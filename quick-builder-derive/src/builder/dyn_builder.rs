@@ -0,0 +1,308 @@
+//! this module generates the dynamic builder, which is requested with
+//! `#[quick_builder(dyn_builder)]`.
+//!
+//! Unlike the typestate builder, the dynamic builder has no generic state.
+//! It stores each field in an `Option` and has `&mut self` setters, so it can
+//! be stored and filled in any order. Whether all fields have been set is
+//! checked at run-time in `try_build()`, which then hands the values over to
+//! the final state of the typestate builder. That way, both builders share
+//! the same code for finishing the instance and checking the invariants.
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Expr, Generics, Type};
+
+use super::{docs::dyn_setter_docs, make_transform_logic, requires_feature};
+use crate::detail::{higher_ranked_bound, hygienic_ident, local_ident, NamedField};
+
+/// everything we need to know to generate the dynamic builder
+pub struct DynBuilderInput<'a> {
+    /// the struct we are building
+    pub original_struct_ident: &'a Ident,
+    /// the typestate builder
+    pub builder_ident: &'a Ident,
+    /// the visibility of the dynamic builder, which is that of the struct
+    pub vis: &'a TokenStream,
+    /// the generics of the struct
+    pub generics: &'a Generics,
+    /// the fields that have a setter, in order of declaration
+    pub set_fields: &'a [NamedField],
+    /// the transform functions of the set fields, in 1-to-1 correspondence
+    pub set_field_transforms: &'a [Option<&'a Expr>],
+    /// the type of the typestate builder where all fields are set
+    pub final_builder: &'a TokenStream,
+    /// whether there are invariants to check
+    pub has_validators: bool,
+    /// whether some of the invariants are async, so that building is async
+    pub has_async_validators: bool,
+    /// the type of the validation context, if any
+    pub context_type: Option<&'a Type>,
+    /// the path to the runtime crate
+    pub runtime_crate: &'a TokenStream,
+    /// the `set_str` option, if the `set_str` function should be generated
+    pub set_str: Option<&'a Ident>,
+    /// the names of the functions that check the invariants of the set fields
+    /// on their own, in 1-to-1 correspondence with the set fields. This is
+    /// `None` for fields whose invariants also depend on other fields or on
+    /// the context, or which don't have invariants.
+    pub set_field_check_fns: &'a [Option<Ident>],
+    /// whether `set_str` can set the field, in 1-to-1 correspondence with the
+    /// set fields. This is false for the fields with `#[builder(no_set_str)]`.
+    pub set_field_parses_str: &'a [bool],
+}
+
+/// the name of the dynamic builder for the given struct
+fn dyn_builder_ident(original_struct_ident: &Ident) -> Ident {
+    format_ident!("{}DynBuilder", original_struct_ident)
+}
+
+/// generate the dynamic builder struct and its implementation
+pub fn make_dyn_builder(input: DynBuilderInput) -> TokenStream {
+    let DynBuilderInput {
+        original_struct_ident,
+        builder_ident,
+        vis,
+        generics,
+        set_fields,
+        set_field_transforms,
+        final_builder,
+        has_validators,
+        has_async_validators,
+        context_type,
+        runtime_crate,
+        set_str,
+        set_field_check_fns,
+        set_field_parses_str,
+    } = input;
+
+    let dyn_builder_ident = dyn_builder_ident(original_struct_ident);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let struct_generics = &generics.params;
+    let original_struct_type = quote! {#original_struct_ident #ty_generics};
+    let error_type = quote! {#runtime_crate::DynBuildError};
+
+    let field_idents = set_fields.iter().map(|f| &f.ident).collect::<Vec<_>>();
+    let field_types = set_fields.iter().map(|f| &f.ty);
+
+    let setters = set_fields
+        .iter()
+        .zip(set_field_transforms)
+        .map(|(field, transform)| {
+            let field_ident = &field.ident;
+            let local = &local_ident(field_ident);
+            let field_type = &field.ty;
+            let transform_logic = transform.map(|transform| make_transform_logic(local, transform));
+            let field_docs = dyn_setter_docs(field);
+            quote! {
+                #field_docs
+                pub fn #field_ident(&mut self, #local: #field_type) -> &mut Self {
+                    #transform_logic
+                    self.#field_ident = ::core::option::Option::Some(#local);
+                    self
+                }
+            }
+        });
+
+    // the first field that is not set is reported as missing
+    let field_names = set_fields.iter().map(|f| f.ident.to_string());
+    let take_fields = quote! {
+        #(
+            let ::core::option::Option::Some(#field_idents) = self.#field_idents else {
+                return ::core::result::Result::Err(#error_type::MissingField(#field_names));
+            };
+        )*
+    };
+
+    // with a context, the function is called try_build_with and takes the
+    // context as an argument, just like the build function of the typestate builder.
+    let context_ident = hygienic_ident("__validation_context");
+    let finished_ident = hygienic_ident("__finished");
+    let description_ident = hygienic_ident("__description");
+    let builder_local = hygienic_ident("__builder");
+    let (try_build_ident, try_build_arguments, context_argument) = match context_type {
+        Some(context_type) => (
+            format_ident!("try_build_with"),
+            quote! {#context_ident: &#context_type},
+            Some(quote! {#context_ident}),
+        ),
+        None => (format_ident!("try_build"), quote! {}, None),
+    };
+
+    // just like for the typestate builder, the async invariants are only
+    // checked if the others hold
+    let check_async_invariants = has_async_validators.then(|| {
+        quote! {
+            if let ::core::result::Result::Err(#description_ident) =
+                <#final_builder>::__check_async_invariants(&#finished_ident, #context_argument).await {
+                return ::core::result::Result::Err(#error_type::InvariantViolated(#description_ident));
+            }
+        }
+    });
+    let check_invariants = has_validators.then(|| {
+        quote! {
+            if let ::core::result::Result::Err(#description_ident) =
+                <#final_builder>::__check_invariants(&#finished_ident, #context_argument) {
+                return ::core::result::Result::Err(#error_type::InvariantViolated(#description_ident));
+            }
+            #check_async_invariants
+        }
+    });
+    let async_qualifier = has_async_validators.then(syn::token::Async::default);
+
+    let set_str_fn = set_str.map(|set_str_option| {
+        make_set_str(
+            set_str_option,
+            runtime_crate,
+            set_fields,
+            set_field_transforms,
+            set_field_check_fns,
+            set_field_parses_str,
+            final_builder,
+        )
+    });
+
+    quote! {
+        /// a builder that checks at run-time whether all fields have been set.
+        #vis struct #dyn_builder_ident <#struct_generics> #where_clause {
+            #(#field_idents: ::core::option::Option<#field_types>,)*
+            __phantom: ::core::marker::PhantomData<#original_struct_type>,
+        }
+
+        impl #impl_generics ::core::default::Default for #dyn_builder_ident #ty_generics #where_clause {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl #impl_generics #dyn_builder_ident #ty_generics #where_clause {
+            /// create a builder where no fields are set yet.
+            pub const fn new() -> Self {
+                Self {
+                    #(#field_idents: ::core::option::Option::None,)*
+                    __phantom: ::core::marker::PhantomData,
+                }
+            }
+
+            #(#setters)*
+
+            /// build the instance, if all fields have been set and all
+            /// invariants hold.
+            pub #async_qualifier fn #try_build_ident(self, #try_build_arguments) -> ::core::result::Result<#original_struct_type, #error_type> {
+                #take_fields
+                let #builder_local: #final_builder = #builder_ident {
+                    state: #runtime_crate::__private::State::new((#(#field_idents,)*)),
+                    phantom: ::core::marker::PhantomData,
+                };
+                let #finished_ident = #builder_local.__finish();
+                #check_invariants
+                ::core::result::Result::Ok(#finished_ident)
+            }
+
+            #set_str_fn
+        }
+
+        impl #impl_generics #original_struct_type #where_clause {
+            /// create a dynamic builder where no fields are set yet.
+            pub const fn dyn_builder() -> #dyn_builder_ident #ty_generics {
+                #dyn_builder_ident::new()
+            }
+        }
+    }
+}
+
+/// generate the function that sets a field from its name and a string, which
+/// is parsed into the type of the field.
+///
+/// The function only exists if all the field types implement `FromStr`,
+/// except for the fields with `#[builder(no_set_str)]`. Those are reported as
+/// unsupported instead of being parsed. The function needs the `alloc` feature
+/// of the runtime crate, see `requires_feature`.
+fn make_set_str(
+    set_str_option: &Ident,
+    runtime_crate: &TokenStream,
+    set_fields: &[NamedField],
+    set_field_transforms: &[Option<&Expr>],
+    set_field_check_fns: &[Option<Ident>],
+    set_field_parses_str: &[bool],
+    final_builder: &TokenStream,
+) -> TokenStream {
+    let error_type = quote! {#runtime_crate::SetError};
+    let name_ident = hygienic_ident("name");
+    let value_ident = hygienic_ident("value");
+    let err_ident = hygienic_ident("__err");
+    let description_ident = hygienic_ident("__description");
+    let parsed_fields = set_fields
+        .iter()
+        .zip(set_field_parses_str)
+        .filter_map(|(field, parses_str)| parses_str.then_some(field));
+    let bounds = parsed_fields.map(|field| {
+        let ty = &field.ty;
+        let from_str = higher_ranked_bound(ty, quote! {::core::str::FromStr});
+        let display_err = higher_ranked_bound(
+            quote! {<#ty as ::core::str::FromStr>::Err},
+            quote! {::core::fmt::Display},
+        );
+        quote! {#from_str, #display_err,}
+    });
+
+    let arms = set_fields
+        .iter()
+        .zip(set_field_transforms)
+        .zip(set_field_check_fns)
+        .zip(set_field_parses_str)
+        .map(|(((field, transform), check_fn), parses_str)| {
+            let field_ident = &field.ident;
+            let local = &local_ident(field_ident);
+            let field_type = &field.ty;
+            let field_name = field.ident.to_string();
+            if !parses_str {
+                return quote! {
+                    #field_name => ::core::result::Result::Err(#error_type::Unsupported(#field_name)),
+                };
+            }
+            let transform_logic =
+                transform.map(|transform| make_transform_logic(local, transform));
+            // the same rules for passing the field apply as when checking
+            // the finished instance.
+            let ref_qualifier = match field.ty {
+                Type::Ptr(_) | Type::Reference(_) => None,
+                _ => Some(quote! {&}),
+            };
+            let check_logic = check_fn.as_ref().map(|check_fn| {
+                quote! {
+                    if let ::core::result::Result::Err(#description_ident) = <#final_builder>::#check_fn(#ref_qualifier #local) {
+                        return ::core::result::Result::Err(#error_type::InvariantViolated(#description_ident));
+                    }
+                }
+            });
+            quote! {
+                #field_name => {
+                    let #local = <#field_type as ::core::str::FromStr>::from_str(#value_ident).map_err(|#err_ident| {
+                        #error_type::Parse {
+                            field: #field_name,
+                            message: #runtime_crate::__private::alloc::string::ToString::to_string(&#err_ident),
+                        }
+                    })?;
+                    #transform_logic
+                    #check_logic
+                    self.#field_ident = ::core::option::Option::Some(#local);
+                    ::core::result::Result::Ok(())
+                }
+            }
+        });
+
+    let set_str_fn = quote! {
+        /// set the field with the given name from a string, which is parsed
+        /// with `FromStr`. The invariants that only depend on the field itself
+        /// are checked right away, the other ones when building.
+        pub fn set_str(&mut self, #name_ident: &str, #value_ident: &str) -> ::core::result::Result<(), #error_type>
+        where
+            #(#bounds)*
+        {
+            match #name_ident {
+                #(#arms)*
+                _ => ::core::result::Result::Err(#error_type::UnknownField(#runtime_crate::__private::alloc::string::ToString::to_string(#name_ident))),
+            }
+        }
+    };
+    requires_feature(runtime_crate, "requires_alloc", set_str_option, set_str_fn)
+}
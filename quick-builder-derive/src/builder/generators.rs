@@ -0,0 +1,188 @@
+//! this module implements proptest's `Arbitrary` and the `Arbitrary` trait of
+//! the arbitrary crate for the struct, which are requested with
+//! `#[quick_builder(proptest)]` and `#[quick_builder(arbitrary)]`.
+//!
+//! The values of the fields are generated independently and then passed
+//! through the builder, so the transforms, skipped fields and normalization
+//! apply just like for values that are built by hand. Instances that violate
+//! the invariants are rejected. For proptest, the strategy of a field can be
+//! given with `#[builder(strategy = ...)]`, which narrows down the generated
+//! values up front, rather than rejecting them afterwards.
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse_quote, Expr, Generics};
+
+use super::requires_feature;
+use crate::detail::{higher_ranked_bound, NamedField};
+
+/// everything we need to know to implement the traits
+pub struct GeneratorInput<'a> {
+    /// the struct we are building
+    pub original_struct_ident: &'a Ident,
+    /// the generics of the struct
+    pub generics: &'a Generics,
+    /// the fields that have a setter, in order of declaration
+    pub set_fields: &'a [NamedField],
+    /// the proptest strategies of the set fields, in 1-to-1 correspondence
+    pub set_field_strategies: &'a [Option<&'a Expr>],
+    /// whether there are invariants, i.e. whether building can fail
+    pub has_validators: bool,
+    /// the path to the runtime crate
+    pub runtime_crate: &'a TokenStream,
+}
+
+/// the identifiers that the generated values of the fields are bound to
+fn value_idents(set_fields: &[NamedField]) -> Vec<Ident> {
+    set_fields
+        .iter()
+        .map(|f| format_ident!("__{}", f.ident.to_string().trim_start_matches("r#")))
+        .collect()
+}
+
+/// the expression that builds the struct from the values bound to the
+/// `value_idents` of the fields.
+fn build_expression(set_fields: &[NamedField], value_idents: &[Ident]) -> TokenStream {
+    let setters = set_fields.iter().map(|f| &f.ident);
+    quote! {
+        Self::builder() #(.#setters(#value_idents))* .build()
+    }
+}
+
+/// implement proptest's `Arbitrary` for the struct.
+///
+/// proptest only implements `Strategy` for tuples of up to 12 elements, so
+/// we combine the strategies of the fields into nested pairs. The
+/// implementation needs the `proptest` feature, see `requires_feature`.
+pub fn make_proptest_arbitrary(proptest_option: &Ident, input: &GeneratorInput) -> TokenStream {
+    let GeneratorInput {
+        original_struct_ident,
+        generics,
+        set_fields,
+        set_field_strategies,
+        has_validators,
+        runtime_crate,
+    } = *input;
+    let proptest = quote! {#runtime_crate::__private::proptest};
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let original_struct_type = quote! {#original_struct_ident #ty_generics};
+
+    let mut generics = generics.clone();
+    let where_clause = generics.make_where_clause();
+    where_clause.predicates.push(higher_ranked_bound(
+        &original_struct_type,
+        quote! {::core::fmt::Debug + 'static},
+    ));
+    for (field, strategy) in set_fields.iter().zip(set_field_strategies) {
+        if strategy.is_none() {
+            where_clause.predicates.push(higher_ranked_bound(
+                &field.ty,
+                quote! {#proptest::arbitrary::Arbitrary},
+            ));
+        }
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let value_idents = value_idents(set_fields);
+    let strategy = set_fields.iter().zip(set_field_strategies).rev().fold(
+        quote! {#proptest::strategy::Just(())},
+        |rest, (field, strategy)| {
+            let ty = &field.ty;
+            let strategy = match strategy {
+                Some(strategy) => quote! {#strategy},
+                None => quote! {#proptest::arbitrary::any::<#ty>()},
+            };
+            quote! {(#strategy, #rest)}
+        },
+    );
+    let pattern = value_idents
+        .iter()
+        .rev()
+        .fold(quote! {()}, |rest, value| quote! {(#value, #rest)});
+    let build_expression = build_expression(set_fields, &value_idents);
+
+    let map = if has_validators {
+        let description = format!("the invariants of `{original_struct_ident}` are violated");
+        quote! {
+            #proptest::strategy::Strategy::prop_filter_map(#strategy, #description, |#pattern| #build_expression)
+        }
+    } else {
+        quote! {
+            #proptest::strategy::Strategy::prop_map(#strategy, |#pattern| #build_expression)
+        }
+    };
+
+    let arbitrary_impl = quote! {
+        impl #impl_generics #proptest::arbitrary::Arbitrary for #original_struct_type #where_clause {
+            type Parameters = ();
+            type Strategy = #proptest::strategy::BoxedStrategy<Self>;
+
+            fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+                #proptest::strategy::Strategy::boxed(#map)
+            }
+        }
+    };
+    requires_feature(
+        runtime_crate,
+        "requires_proptest",
+        proptest_option,
+        arbitrary_impl,
+    )
+}
+
+/// implement `Arbitrary` of the arbitrary crate for the struct. If the
+/// invariants are violated, the input is rejected as incorrectly formatted,
+/// which tells the fuzzer to try something else. The implementation needs the
+/// `arbitrary` feature, see `requires_feature`.
+pub fn make_arbitrary(arbitrary_option: &Ident, input: &GeneratorInput) -> TokenStream {
+    let GeneratorInput {
+        original_struct_ident,
+        generics,
+        set_fields,
+        has_validators,
+        runtime_crate,
+        ..
+    } = *input;
+    let arbitrary = quote! {#runtime_crate::__private::arbitrary};
+    let (_, ty_generics, _) = generics.split_for_impl();
+
+    let mut generics = generics.clone();
+    generics.params.insert(0, parse_quote! {'__arbitrary});
+    let where_clause = generics.make_where_clause();
+    for field in set_fields {
+        let ty = &field.ty;
+        where_clause.predicates.push(parse_quote! {
+            #ty: #arbitrary::Arbitrary<'__arbitrary>
+        });
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let value_idents = value_idents(set_fields);
+    let field_types = set_fields.iter().map(|f| &f.ty);
+    let build_expression = build_expression(set_fields, &value_idents);
+    let result = if has_validators {
+        quote! {
+            #build_expression.ok_or(#arbitrary::Error::IncorrectFormat)
+        }
+    } else {
+        quote! {
+            ::core::result::Result::Ok(#build_expression)
+        }
+    };
+
+    let arbitrary_impl = quote! {
+        impl #impl_generics #arbitrary::Arbitrary<'__arbitrary> for #original_struct_ident #ty_generics #where_clause {
+            fn arbitrary(u: &mut #arbitrary::Unstructured<'__arbitrary>) -> #arbitrary::Result<Self> {
+                #(
+                    let #value_idents = <#field_types as #arbitrary::Arbitrary<'__arbitrary>>::arbitrary(u)?;
+                )*
+                #result
+            }
+        }
+    };
+    requires_feature(
+        runtime_crate,
+        "requires_arbitrary",
+        arbitrary_option,
+        arbitrary_impl,
+    )
+}
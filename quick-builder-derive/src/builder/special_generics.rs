@@ -30,6 +30,50 @@
 use quote::ToTokens;
 use syn::{GenericParam, Generics, Token};
 
+/// the generics with the defaults of the type and const parameters removed.
+/// We need those for declaring the builder, because it has an additional
+/// generic parameter for its state after the generics of the struct and
+/// parameters with defaults must come last.
+pub fn without_defaults(generics: &Generics) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.params.iter_mut() {
+        match param {
+            GenericParam::Type(param) => {
+                param.eq_token = None;
+                param.default = None;
+            }
+            GenericParam::Const(param) => {
+                param.eq_token = None;
+                param.default = None;
+            }
+            GenericParam::Lifetime(_) => {}
+        }
+    }
+    generics
+}
+
+/// the generics with all bounds and the where clause removed, but with the
+/// defaults kept. We need those for declaring type aliases of the builder,
+/// because bounds on type aliases are not enforced and give a warning.
+pub fn without_bounds(generics: &Generics) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.params.iter_mut() {
+        match param {
+            GenericParam::Lifetime(param) => {
+                param.colon_token = None;
+                param.bounds.clear();
+            }
+            GenericParam::Type(param) => {
+                param.colon_token = None;
+                param.bounds.clear();
+            }
+            GenericParam::Const(_) => {}
+        }
+    }
+    generics.where_clause = None;
+    generics
+}
+
 /// a helper type that is stolen from the syn crate. It gives us the type generics
 /// without the enclosing <...> brackets.
 pub struct TypeGenericsWithoutAngleBrackets<'a>(&'a Generics);
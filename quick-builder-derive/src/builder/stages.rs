@@ -0,0 +1,231 @@
+//! this module generates type aliases for the stages of the builder, so that
+//! partially filled builders can be named without spelling out the tuple of
+//! field types that makes up the state. Those are only generated with
+//! `#[quick_builder(stage_aliases)]`, since they add names next to the struct.
+//!
+//! For a builder `FooBuilder` with the fields `width` and `height`, those are
+//! `FooBuilderEmpty`, `FooBuilderAfterWidth`, `FooBuilderAfterHeight` and
+//! `FooBuilderComplete`, where the last two are the same type.
+//!
+//! With `#[quick_builder(stage_traits)]`, it also implements the traits of the
+//! runtime crate for the stages, which allow writing generic code over them.
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Generics, ImplGenerics, Index, WhereClause};
+
+use super::special_generics::without_bounds;
+use crate::{
+    detail::{nested_visibility, NamedField},
+    error::CompileError,
+};
+
+/// the field name in upper camel case, e.g. `max_height` becomes `MaxHeight`.
+fn upper_camel_case(field_ident: &Ident) -> String {
+    let name = field_ident.to_string();
+    name.trim_start_matches("r#")
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// generate the aliases for all stages of the builder.
+///
+/// The `builder_type_with_count` closure must give the builder type where the
+/// given count of fields has been initialized. Returns an error if two fields
+/// give the same alias, like `len` and `_len`.
+pub fn make_stage_aliases(
+    original_struct_ident: &Ident,
+    builder_ident: &Ident,
+    vis: &TokenStream,
+    generics: &Generics,
+    set_fields: &[NamedField],
+    builder_type_with_count: impl Fn(usize) -> TokenStream,
+) -> Result<TokenStream, CompileError> {
+    let alias_generics = without_bounds(generics);
+
+    let empty = (
+        format_ident!("{}Empty", builder_ident),
+        format!("the builder for [`{original_struct_ident}`] before any field is set."),
+        0,
+    );
+    // the aliases after the fields come with their field, so that we can point
+    // at the fields if two of them give the same alias. The empty and complete
+    // aliases can't clash with those, because they don't contain `After`.
+    let after_fields = set_fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            (
+                &field.ident,
+                format_ident!("{}After{}", builder_ident, upper_camel_case(&field.ident)),
+                format!(
+                    "the builder for [`{original_struct_ident}`] after the field `{}` is set.",
+                    field.ident
+                ),
+                index + 1,
+            )
+        })
+        .collect::<Vec<_>>();
+    for (index, (field_ident, alias_ident, _, _)) in after_fields.iter().enumerate() {
+        if let Some((earlier_field_ident, ..)) = after_fields[..index]
+            .iter()
+            .find(|(_, other, _, _)| other == alias_ident)
+        {
+            return Err(CompileError::new_spanned(
+                field_ident,
+                format!(
+                    "the fields `{earlier_field_ident}` and `{field_ident}` both give the stage alias `{alias_ident}`, \
+                    rename one of them or remove the `stage_aliases` option",
+                ),
+            ));
+        }
+    }
+    let complete = (
+        format_ident!("{}Complete", builder_ident),
+        format!("the builder for [`{original_struct_ident}`] after all fields are set."),
+        set_fields.len(),
+    );
+
+    let stages = std::iter::once(empty)
+        .chain(
+            after_fields
+                .into_iter()
+                .map(|(_, alias_ident, docs, count)| (alias_ident, docs, count)),
+        )
+        .chain(std::iter::once(complete))
+        .collect::<Vec<_>>();
+
+    let aliases = stages.iter().map(|(alias_ident, docs, count)| {
+        let builder_type = builder_type_with_count(*count);
+        quote! {
+            #[doc = #docs]
+            #vis type #alias_ident #alias_generics = #builder_type;
+        }
+    });
+
+    Ok(quote! {#(#aliases)*})
+}
+
+/// the name of the module with the field markers for the given struct
+fn fields_module_ident(original_struct_ident: &Ident) -> Ident {
+    format_ident!("{}Fields", original_struct_ident)
+}
+
+/// generate the module with the field markers, which identify the fields in
+/// `HasField` and `SetField`.
+///
+/// The markers have the visibility of their fields, so that the trait
+/// implementations don't make private fields any more visible.
+pub fn make_fields_module(
+    original_struct_ident: &Ident,
+    vis: &TokenStream,
+    set_fields: &[NamedField],
+) -> TokenStream {
+    let fields_module_ident = fields_module_ident(original_struct_ident);
+    let module_docs = format!(
+        "the markers for the fields of `{original_struct_ident}`, which identify \
+        the fields in the `HasField` and `SetField` traits."
+    );
+    let markers = set_fields.iter().map(|field| {
+        let field_ident = &field.ident;
+        let docs = format!("the marker for the field `{field_ident}`");
+        let marker_vis = nested_visibility(&field.vis, 1);
+        quote! {
+            #[doc = #docs]
+            #[allow(non_camel_case_types)]
+            #marker_vis enum #field_ident {}
+        }
+    });
+    quote! {
+        #[doc = #module_docs]
+        #[allow(non_snake_case)]
+        #vis mod #fields_module_ident {
+            #(#markers)*
+        }
+    }
+}
+
+/// generate the implementations of `HasField`, `SetField` and `IsComplete`
+/// for the stages of the builder.
+///
+/// The `builder_type_with_count` closure must give the builder type where the
+/// given count of fields has been initialized. The `build_return_type` is
+/// `None` if the builder can't implement `IsComplete`, e.g. because it has a
+/// validation context.
+pub fn make_stage_traits(
+    original_struct_ident: &Ident,
+    set_fields: &[NamedField],
+    impl_generics: &ImplGenerics,
+    where_clause: Option<&WhereClause>,
+    runtime_crate: &TokenStream,
+    builder_type_with_count: impl Fn(usize) -> TokenStream,
+    build_return_type: Option<&TokenStream>,
+) -> TokenStream {
+    let fields_module_ident = fields_module_ident(original_struct_ident);
+
+    // the field is set in all stages after its own, so there is one
+    // implementation for each of them.
+    let has_field_impls = set_fields.iter().enumerate().flat_map(|(index, field)| {
+        let field_ident = &field.ident;
+        let field_type = &field.ty;
+        let tuple_index = Index::from(index);
+        let fields_module_ident = &fields_module_ident;
+        let builder_type_with_count = &builder_type_with_count;
+        (index + 1..=set_fields.len()).map(move |count| {
+            let builder_type = builder_type_with_count(count);
+            quote! {
+                impl #impl_generics #runtime_crate::HasField<#fields_module_ident::#field_ident> for #builder_type #where_clause {
+                    type Value = #field_type;
+
+                    fn field(&self) -> &Self::Value {
+                        &self.state.get().#tuple_index
+                    }
+                }
+            }
+        })
+    });
+
+    let set_field_impls = set_fields.iter().enumerate().map(|(index, field)| {
+        let field_ident = &field.ident;
+        let field_type = &field.ty;
+        let builder_type = builder_type_with_count(index);
+        let next_builder_type = builder_type_with_count(index + 1);
+        quote! {
+            impl #impl_generics #runtime_crate::SetField<#fields_module_ident::#field_ident> for #builder_type #where_clause {
+                type Value = #field_type;
+                type Next = #next_builder_type;
+
+                fn set(self, value: Self::Value) -> Self::Next {
+                    self.#field_ident(value)
+                }
+            }
+        }
+    });
+
+    let is_complete_impl = build_return_type.map(|build_return_type| {
+        let final_builder = builder_type_with_count(set_fields.len());
+        quote! {
+            impl #impl_generics #runtime_crate::IsComplete for #final_builder #where_clause {
+                type Output = #build_return_type;
+
+                fn build(self) -> Self::Output {
+                    self.build()
+                }
+            }
+        }
+    });
+
+    quote! {
+        #(#has_field_impls)*
+
+        #(#set_field_impls)*
+
+        #is_complete_impl
+    }
+}
@@ -1,43 +1,198 @@
-use proc_macro2::Ident;
-use syn::{Attribute, Data, DataStruct, DeriveInput, Generics, Visibility};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{
+    parse_quote, Attribute, Data, DeriveInput, Field, Fields, Generics, Path, Type, Visibility,
+    WherePredicate,
+};
 
 use crate::error::CompileError;
 
 /// this is syn's DeriveInput where we know that the contained data is a struct
-/// and not anything else
+/// with at least one named field and not anything else
 #[allow(dead_code)]
 pub struct StructDeriveInput {
     pub attrs: Vec<Attribute>,
     pub vis: Visibility,
     pub ident: Ident,
     pub generics: Generics,
-    pub data: DataStruct,
+    pub fields: Vec<NamedField>,
+}
+
+/// this is syn's Field where we know that the field has a name
+#[allow(dead_code)]
+#[derive(Clone)]
+pub struct NamedField {
+    pub attrs: Vec<Attribute>,
+    pub vis: Visibility,
+    pub ident: Ident,
+    pub ty: Type,
+}
+
+/// the visibility that an item declared `depth` modules below the given item
+/// needs to have the same visibility as that item. E.g. a private struct is
+/// visible in its module, so an item in a child module must be `pub(super)`.
+pub fn nested_visibility(vis: &Visibility, depth: usize) -> TokenStream {
+    // repeat_n would need a newer compiler than the rest of the crate
+    #[allow(clippy::manual_repeat_n)]
+    let supers = std::iter::repeat(quote! {super}).take(depth);
+    match vis {
+        Visibility::Public(_) => quote! {pub},
+        Visibility::Inherited => quote! {pub(in #(#supers)::*)},
+        Visibility::Restricted(restricted) => {
+            let path = &restricted.path;
+            if path.segments.first().is_some_and(|s| s.ident == "crate") {
+                return quote! {#vis};
+            }
+            // the path is relative, i.e. it starts with self or super
+            let segments = path
+                .segments
+                .iter()
+                .skip_while(|s| s.ident == "self")
+                .map(|s| quote! {#s});
+            let segments = supers.chain(segments);
+            quote! {pub(in #(#segments)::*)}
+        }
+    }
+}
+
+/// an identifier for the generated code that the code written by the user,
+/// like the closures and expressions in the attributes, can't see. That way,
+/// the local variables and helpers of the generated code can't clash with
+/// the names the user refers to.
+pub fn hygienic_ident(name: &str) -> Ident {
+    Ident::new(name, Span::mixed_site())
+}
+
+/// the identifier of a field for a local variable or argument of the
+/// generated code. It has the call site span rather than the span of the
+/// field, so that lints about bindings like `clippy::disallowed_names` don't
+/// point at a field the user can't do anything about. User code like the skip
+/// expressions still resolves it, because the call site span is not hygienic.
+pub fn local_ident(field_ident: &Ident) -> Ident {
+    let mut ident = field_ident.clone();
+    ident.set_span(Span::call_site());
+    ident
+}
+
+/// the where predicate `ty: bound`, but higher ranked over a lifetime that
+/// is never used. The generated code puts bounds on the concrete field types
+/// to make impls and methods only exist if the types implement a trait. A
+/// bound like `NotDebug: Debug` without any generics in it would be trivially
+/// false, which rustc reports as an error at the definition rather than just
+/// leaving out the impl or method. The higher ranked bound is not checked
+/// until it is used.
+pub fn higher_ranked_bound(ty: impl ToTokens, bound: impl ToTokens) -> WherePredicate {
+    parse_quote! {for<'__bound> #ty: #bound}
+}
+
+/// whether the path names the item of the standard library with the given
+/// name in the given module, i.e. it is either just the name or the full path
+/// through `core` or `std`, like `Option`, `core::option::Option` or
+/// `::std::option::Option`. Other paths that end in the name belong to user
+/// types that shadow the item, so they don't count. The generic arguments of
+/// the last segment are not looked at.
+pub fn is_std_path(path: &Path, module: &str, name: &str) -> bool {
+    let segments = path.segments.iter().collect::<Vec<_>>();
+    let Some((last, modules)) = segments.split_last() else {
+        return false;
+    };
+    if last.ident != name || modules.iter().any(|s| !s.arguments.is_none()) {
+        return false;
+    }
+    match modules {
+        [] => path.leading_colon.is_none(),
+        [krate, mod_] => (krate.ident == "core" || krate.ident == "std") && mod_.ident == module,
+        _ => false,
+    }
+}
+
+/// whether the type is a `PhantomData`. We can only go by the path here, so
+/// this is a bare `PhantomData` or its full path, see `is_std_path`.
+pub fn is_phantom_data(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => {
+            is_std_path(&path.path, "marker", "PhantomData")
+        }
+        Type::Paren(paren) => is_phantom_data(&paren.elem),
+        Type::Group(group) => is_phantom_data(&group.elem),
+        _ => false,
+    }
 }
 
 const EXPECTED_STRUCT_ERROR: &str = "Expected struct: QuickBuilder can only be derived on structs";
 
-/// get an instance from the derive input. If this is not a struct, then
-/// returns an error.
+/// get an instance from the derive input. If this is not a struct with named
+/// fields, then returns an error.
 impl TryFrom<DeriveInput> for StructDeriveInput {
     type Error = CompileError;
 
     fn try_from(input: DeriveInput) -> Result<Self, Self::Error> {
-        match input.data {
-            Data::Struct(data) => Ok(Self {
-                attrs: input.attrs,
-                vis: input.vis,
-                ident: input.ident,
-                generics: input.generics,
-                data,
-            }),
-            Data::Enum(data) => Err(CompileError::new_spanned(
-                data.enum_token,
-                EXPECTED_STRUCT_ERROR,
-            )),
-            Data::Union(data) => Err(CompileError::new_spanned(
-                data.union_token,
-                EXPECTED_STRUCT_ERROR,
+        let data = match input.data {
+            Data::Struct(data) => data,
+            Data::Enum(data) => {
+                return Err(CompileError::new_spanned(
+                    data.enum_token,
+                    format!("{EXPECTED_STRUCT_ERROR}, not on enums"),
+                ))
+            }
+            Data::Union(data) => {
+                return Err(CompileError::new_spanned(
+                    data.union_token,
+                    format!("{EXPECTED_STRUCT_ERROR}, not on unions"),
+                ))
+            }
+        };
+
+        let fields = match data.fields {
+            Fields::Named(named) if named.named.is_empty() => {
+                return Err(CompileError::new_spanned(
+                    &input.ident,
+                    "QuickBuilder: not possible to derive on struct without fields",
+                ))
+            }
+            Fields::Named(named) => named
+                .named
+                .into_iter()
+                .map(NamedField::try_from)
+                .collect::<Result<Vec<_>, _>>()?,
+            Fields::Unnamed(unnamed) => return Err(CompileError::new_spanned(
+                unnamed,
+                "QuickBuilder: not possible to derive on tuple structs, the fields must be named",
             )),
-        }
+            Fields::Unit => {
+                return Err(CompileError::new_spanned(
+                    &input.ident,
+                    "QuickBuilder: not possible to derive on unit structs",
+                ))
+            }
+        };
+
+        Ok(Self {
+            attrs: input.attrs,
+            vis: input.vis,
+            ident: input.ident,
+            generics: input.generics,
+            fields,
+        })
+    }
+}
+
+/// get a named field from a field. If the field has no name, returns an error.
+impl TryFrom<Field> for NamedField {
+    type Error = CompileError;
+
+    fn try_from(field: Field) -> Result<Self, Self::Error> {
+        let Some(ident) = field.ident else {
+            return Err(CompileError::new_spanned(
+                &field.ty,
+                "QuickBuilder: all fields must be named",
+            ));
+        };
+        Ok(Self {
+            attrs: field.attrs,
+            vis: field.vis,
+            ident,
+            ty: field.ty,
+        })
     }
 }
@@ -16,6 +16,12 @@ impl From<syn::Error> for CompileError {
     }
 }
 
+impl From<CompileError> for syn::Error {
+    fn from(error: CompileError) -> Self {
+        error.inner
+    }
+}
+
 impl From<CompileError> for TokenStream {
     fn from(error: CompileError) -> Self {
         error.inner.into_compile_error().into()
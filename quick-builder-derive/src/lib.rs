@@ -6,6 +6,7 @@ use syn::{parse_macro_input, DeriveInput};
 mod builder;
 mod detail;
 mod error;
+mod options;
 mod validation;
 
 /// helper macro for returning compile errors even in functions which don't return
@@ -21,7 +22,7 @@ macro_rules! try2 {
     };
 }
 
-#[proc_macro_derive(QuickBuilder, attributes(invariant))]
+#[proc_macro_derive(QuickBuilder, attributes(invariant, quick_builder, builder))]
 pub fn quick_builder(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input: StructDeriveInput = try2!(parse_macro_input!(input as DeriveInput).try_into());
 
@@ -0,0 +1,413 @@
+//! this module parses the options for the builder, which are given in the
+//! `#[quick_builder(...)]` attribute on the struct, and the options for the
+//! individual fields, which are given in the `#[builder(...)]` attribute.
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{meta::ParseNestedMeta, parse_quote, Attribute, Expr, Path, Type};
+
+use crate::detail::{is_phantom_data, NamedField};
+use crate::error::CompileError;
+
+const QUICK_BUILDER_ATTR: &str = "quick_builder";
+const FIELD_ATTR: &str = "builder";
+
+/// the traits that can be derived on the builder with
+/// `#[quick_builder(derive(...))]`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BuilderDerive {
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+}
+
+impl BuilderDerive {
+    const ALL: [BuilderDerive; 6] = [
+        BuilderDerive::Clone,
+        BuilderDerive::Copy,
+        BuilderDerive::Debug,
+        BuilderDerive::PartialEq,
+        BuilderDerive::Eq,
+        BuilderDerive::Hash,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            BuilderDerive::Clone => "Clone",
+            BuilderDerive::Copy => "Copy",
+            BuilderDerive::Debug => "Debug",
+            BuilderDerive::PartialEq => "PartialEq",
+            BuilderDerive::Eq => "Eq",
+            BuilderDerive::Hash => "Hash",
+        }
+    }
+}
+
+impl TryFrom<&Ident> for BuilderDerive {
+    type Error = CompileError;
+
+    fn try_from(ident: &Ident) -> Result<Self, Self::Error> {
+        BuilderDerive::ALL
+            .into_iter()
+            .find(|derive| ident == derive.name())
+            .ok_or_else(|| {
+                let supported = BuilderDerive::ALL.map(BuilderDerive::name).join(", ");
+                CompileError::new_spanned(
+                    ident,
+                    format!("trait `{ident}` can't be derived on the builder, supported traits are: {supported}"),
+                )
+            })
+    }
+}
+
+/// the options given in the (zero or more) `#[quick_builder(...)]` attributes
+/// on the struct.
+#[derive(Default)]
+pub struct BuilderOptions {
+    /// the traits to derive on the builder, together with the identifier
+    /// that was given in the attribute
+    derives: Vec<(BuilderDerive, Ident)>,
+    /// a function taking `&mut Foo`, which is called on the finished struct
+    /// before the invariants are checked
+    normalize: Option<Expr>,
+    /// the type of the context that is passed to all invariants
+    context: Option<Type>,
+    /// whether to generate the dynamic builder in addition to the typestate
+    /// builder
+    dyn_builder: bool,
+    /// whether to generate the `set_str` function on the dynamic builder
+    set_str: Option<Ident>,
+    /// whether to implement proptest's `Arbitrary` through the builder
+    proptest: Option<Ident>,
+    /// whether to implement `Arbitrary` of the arbitrary crate through the
+    /// builder
+    arbitrary: Option<Ident>,
+    /// whether to generate the type aliases for the stages of the builder
+    stage_aliases: Option<Ident>,
+    /// whether to generate the field markers and implement the stage traits
+    /// of the runtime crate for the builder
+    stage_traits: bool,
+    /// the path to the runtime crate, if it is not `::quick_builder`
+    runtime_crate: Option<Path>,
+}
+
+impl BuilderOptions {
+    /// parse the options from the attributes of the struct. Attributes that
+    /// are not `#[quick_builder(...)]` are ignored.
+    pub fn new(attributes: &[Attribute]) -> Result<Self, CompileError> {
+        let mut options = Self::default();
+        for attr in attributes
+            .iter()
+            .filter(|attr| attr.path().is_ident(QUICK_BUILDER_ATTR))
+        {
+            attr.parse_nested_meta(|meta| options.parse_option(meta))?;
+        }
+        options.check()?;
+        Ok(options)
+    }
+
+    /// the traits to derive on the builder
+    pub fn derives(&self) -> impl Iterator<Item = BuilderDerive> + '_ {
+        self.derives.iter().map(|(derive, _)| *derive)
+    }
+
+    /// the type of the validation context, if any
+    pub fn context(&self) -> Option<&Type> {
+        self.context.as_ref()
+    }
+
+    /// whether the dynamic builder should be generated
+    pub fn dyn_builder(&self) -> bool {
+        self.dyn_builder
+    }
+
+    /// the option that requested the `set_str` function on the dynamic
+    /// builder, if any
+    pub fn set_str(&self) -> Option<&Ident> {
+        self.set_str.as_ref()
+    }
+
+    /// the option that requested implementing proptest's `Arbitrary` for the
+    /// struct, if any
+    pub fn proptest(&self) -> Option<&Ident> {
+        self.proptest.as_ref()
+    }
+
+    /// the option that requested implementing `Arbitrary` of the arbitrary
+    /// crate for the struct, if any
+    pub fn arbitrary(&self) -> Option<&Ident> {
+        self.arbitrary.as_ref()
+    }
+
+    /// the option that requested the type aliases for the stages of the
+    /// builder, if any
+    pub fn stage_aliases(&self) -> Option<&Ident> {
+        self.stage_aliases.as_ref()
+    }
+
+    /// whether the field markers and the stage traits should be generated
+    pub fn stage_traits(&self) -> bool {
+        self.stage_traits
+    }
+
+    /// the option that requested implementing one of the `Arbitrary` traits,
+    /// if any
+    pub fn generator(&self) -> Option<&Ident> {
+        self.proptest.as_ref().or(self.arbitrary.as_ref())
+    }
+
+    /// the path to the runtime crate, which the generated code uses for the
+    /// traits and helpers. This is `::quick_builder` unless it was given
+    /// with `#[quick_builder(crate = path)]`, e.g. for a renamed dependency.
+    pub fn runtime_crate(&self) -> TokenStream {
+        match &self.runtime_crate {
+            Some(path) => path.to_token_stream(),
+            None => quote! {::quick_builder},
+        }
+    }
+
+    /// the function that normalizes the finished struct, if any
+    pub fn normalize(&self) -> Option<&Expr> {
+        self.normalize.as_ref()
+    }
+
+    /// parse a single option inside the braces of the attribute
+    fn parse_option(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("derive") {
+            meta.parse_nested_meta(|derive| {
+                let Some(ident) = derive.path.get_ident() else {
+                    return Err(derive.error("expected the name of a trait"));
+                };
+                let builder_derive = BuilderDerive::try_from(ident)?;
+                if self.derives().any(|d| d == builder_derive) {
+                    return Err(derive.error("trait must not be given more than once"));
+                }
+                self.derives.push((builder_derive, ident.clone()));
+                Ok(())
+            })
+        } else if meta.path.is_ident("normalize") {
+            if self.normalize.is_some() {
+                return Err(meta.error("`normalize` must not be given more than once"));
+            }
+            self.normalize = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("context") {
+            if self.context.is_some() {
+                return Err(meta.error("`context` must not be given more than once"));
+            }
+            self.context = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("dyn_builder") {
+            if self.dyn_builder {
+                return Err(meta.error("`dyn_builder` must not be given more than once"));
+            }
+            self.dyn_builder = true;
+            Ok(())
+        } else if meta.path.is_ident("set_str") {
+            if self.set_str.is_some() {
+                return Err(meta.error("`set_str` must not be given more than once"));
+            }
+            self.set_str = meta.path.get_ident().cloned();
+            Ok(())
+        } else if meta.path.is_ident("proptest") {
+            if self.proptest.is_some() {
+                return Err(meta.error("`proptest` must not be given more than once"));
+            }
+            self.proptest = meta.path.get_ident().cloned();
+            Ok(())
+        } else if meta.path.is_ident("arbitrary") {
+            if self.arbitrary.is_some() {
+                return Err(meta.error("`arbitrary` must not be given more than once"));
+            }
+            self.arbitrary = meta.path.get_ident().cloned();
+            Ok(())
+        } else if meta.path.is_ident("stage_aliases") {
+            if self.stage_aliases.is_some() {
+                return Err(meta.error("`stage_aliases` must not be given more than once"));
+            }
+            self.stage_aliases = meta.path.get_ident().cloned();
+            Ok(())
+        } else if meta.path.is_ident("stage_traits") {
+            if self.stage_traits {
+                return Err(meta.error("`stage_traits` must not be given more than once"));
+            }
+            self.stage_traits = true;
+            Ok(())
+        } else if meta.path.is_ident("crate") {
+            if self.runtime_crate.is_some() {
+                return Err(meta.error("`crate` must not be given more than once"));
+            }
+            self.runtime_crate = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error(format!(
+                "unknown option for #[{QUICK_BUILDER_ATTR}(...)], the allowed options are `derive(...)`, `normalize = ...`, `context = ...`, `dyn_builder`, `set_str`, `proptest`, `arbitrary`, `stage_aliases`, `stage_traits` and `crate = ...`"
+            )))
+        }
+    }
+
+    /// sanity checks on the options after all of them have been parsed
+    fn check(&self) -> Result<(), CompileError> {
+        // just like for the std derives, the marker traits require their
+        // supertraits to be derived as well.
+        let requirements = [
+            (BuilderDerive::Copy, BuilderDerive::Clone),
+            (BuilderDerive::Eq, BuilderDerive::PartialEq),
+        ];
+        for (derive, required) in requirements {
+            let Some((_, ident)) = self.derives.iter().find(|(d, _)| *d == derive) else {
+                continue;
+            };
+            if !self.derives().any(|d| d == required) {
+                return Err(CompileError::new_spanned(
+                    ident,
+                    format!(
+                        "deriving `{}` on the builder requires deriving `{}` as well",
+                        derive.name(),
+                        required.name()
+                    ),
+                ));
+            }
+        }
+        // set_str is a function on the dynamic builder
+        if let (Some(set_str), false) = (&self.set_str, self.dyn_builder) {
+            return Err(CompileError::new_spanned(
+                set_str,
+                "`set_str` requires the dynamic builder, add the `dyn_builder` option as well",
+            ));
+        }
+        // the generated values are built without a context, since there is
+        // no way to generate one that makes sense
+        if let (Some(_), Some(generator)) = (&self.context, self.generator()) {
+            return Err(CompileError::new_spanned(
+                generator,
+                format!("`{generator}` can't be used together with a validation context"),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// the options given in the (zero or more) `#[builder(...)]` attributes on a
+/// field.
+#[derive(Default)]
+pub struct FieldOptions {
+    /// if this is given, the field has no setter and is instead computed
+    /// from this expression in the build function.
+    skip: Option<Expr>,
+    /// if this is given, the value passed to the setter is transformed with
+    /// this function before it is stored in the builder.
+    transform: Option<Expr>,
+    /// whether the field is a `PhantomData` that is skipped automatically.
+    skips_phantom_data: bool,
+    /// the proptest strategy for generating values of the field, instead
+    /// of the one from its `Arbitrary` implementation.
+    strategy: Option<Expr>,
+    /// if this is given, `set_str` does not parse the field, so that its type
+    /// does not have to implement `FromStr`.
+    no_set_str: Option<Ident>,
+}
+
+impl FieldOptions {
+    /// parse the options from the attributes of the field. Attributes that
+    /// are not `#[builder(...)]` are ignored. `PhantomData` fields are skipped
+    /// without having to say so, since a setter for them would be useless.
+    pub fn new(field: &NamedField) -> Result<Self, CompileError> {
+        let mut options = Self::default();
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident(FIELD_ATTR))
+        {
+            attr.parse_nested_meta(|meta| options.parse_option(meta))?;
+        }
+        if let (Some(_), Some(strategy)) = (&options.skip, &options.strategy) {
+            return Err(CompileError::new_spanned(
+                strategy,
+                "`strategy` can't be used on skipped fields",
+            ));
+        }
+        if let (Some(_), Some(no_set_str)) = (&options.skip, &options.no_set_str) {
+            return Err(CompileError::new_spanned(
+                no_set_str,
+                "`no_set_str` can't be used on skipped fields",
+            ));
+        }
+        if options.skip.is_none() && is_phantom_data(&field.ty) {
+            options.skip = Some(parse_quote!(::core::marker::PhantomData));
+            options.skips_phantom_data = true;
+        }
+        // a skipped field has no setter, so there is nothing to transform
+        if let (Some(_), Some(transform)) = (&options.skip, &options.transform) {
+            return Err(CompileError::new_spanned(
+                transform,
+                "`transform` can't be used on skipped fields",
+            ));
+        }
+        Ok(options)
+    }
+
+    /// the function that transforms the value given to the setter, if any
+    pub fn transform(&self) -> Option<&Expr> {
+        self.transform.as_ref()
+    }
+
+    /// the expression that computes the value of a skipped field, if the
+    /// field is skipped.
+    pub fn skip(&self) -> Option<&Expr> {
+        self.skip.as_ref()
+    }
+
+    /// the proptest strategy for the field, if given
+    pub fn strategy(&self) -> Option<&Expr> {
+        self.strategy.as_ref()
+    }
+
+    /// whether `set_str` can't set the field, because it was opted out with
+    /// `no_set_str`
+    pub fn no_set_str(&self) -> bool {
+        self.no_set_str.is_some()
+    }
+
+    /// whether the field is a `PhantomData` that was skipped automatically.
+    /// Unlike other skipped fields, we know that those can be computed in a
+    /// const context.
+    pub fn skips_phantom_data(&self) -> bool {
+        self.skips_phantom_data
+    }
+
+    /// parse a single option inside the braces of the attribute
+    fn parse_option(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("skip") {
+            if self.skip.is_some() {
+                return Err(meta.error("`skip` must not be given more than once"));
+            }
+            self.skip = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("transform") {
+            if self.transform.is_some() {
+                return Err(meta.error("`transform` must not be given more than once"));
+            }
+            self.transform = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("strategy") {
+            if self.strategy.is_some() {
+                return Err(meta.error("`strategy` must not be given more than once"));
+            }
+            self.strategy = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("no_set_str") {
+            if self.no_set_str.is_some() {
+                return Err(meta.error("`no_set_str` must not be given more than once"));
+            }
+            self.no_set_str = meta.path.get_ident().cloned();
+            Ok(())
+        } else {
+            Err(meta.error(format!(
+                "unknown option for #[{FIELD_ATTR}(...)], the allowed options are `skip = ...`, `transform = ...`, `strategy = ...` and `no_set_str`"
+            )))
+        }
+    }
+}
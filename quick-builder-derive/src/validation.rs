@@ -3,13 +3,18 @@
 //! one argument returning a bool, or paths that must point to a function
 //! with one argument returning a bool. The argument must be of type
 //! `&Foo` where `Foo` is the structure for which we created the builder.
-use proc_macro2::Span;
+//! Closures on fields may have additional arguments, which are named like
+//! fields declared before, e.g. #[invariant(|h, max_height| h <= max_height)].
+use proc_macro2::{Ident, Span};
 use quote::ToTokens;
-use syn::{spanned::Spanned, visit::Visit, Attribute, ExprClosure, Meta, Path};
+use syn::{
+    ext::IdentExt, parse::ParseStream, spanned::Spanned, visit::Visit, Attribute, ExprClosure,
+    Meta, Pat, Path, Token,
+};
 
 const INVARIANT_ATTR: &str = "invariant";
 
-use crate::{builder::FINISHED_VALUE_IDENT, error::CompileError};
+use crate::{builder::FINISHED_VALUE_IDENT, detail::NamedField, error::CompileError};
 
 // #[derive(Debug)]
 /// the invariant-attribute:
@@ -25,9 +30,32 @@ use crate::{builder::FINISHED_VALUE_IDENT, error::CompileError};
 ///
 /// If the attribute is on the struct, the finished struct itself is passed for
 /// validation before it is built.
+///
+/// Closures on fields can take more than one argument: the additional arguments
+/// are bindings to other fields of the same name, which are passed just like
+/// the field itself, e.g. `#[invariant(|h, max_height| h <= max_height)]`.
+///
+/// If the builder has a validation context, all invariants (closures and
+/// functions) receive a reference to the context as their last argument.
+///
+/// Additionally, the attribute can be marked as `const` like so:
+/// `#[invariant(function_name, const)]`, which means that the function is a
+/// `const fn` and can be evaluated at compile time. It can also be marked as
+/// `debug_only`, which means that it is only checked when `debug_assertions`
+/// are enabled.
+///
+/// Paths can be prefixed with `async`, like `#[invariant(async function_name)]`,
+/// which means that the function is an `async fn` and the build function has
+/// to await it.
 pub struct InvariantAttribute {
     /// the expression in brackets in the validation attribute
     expression: InvariantExpression,
+    /// whether the invariant was flagged as `const`
+    is_const: bool,
+    /// whether the invariant was flagged as `debug_only`
+    is_debug_only: bool,
+    /// whether the invariant is an async function
+    is_async: bool,
 }
 
 impl InvariantAttribute {
@@ -40,6 +68,67 @@ impl InvariantAttribute {
     pub fn expression_span(&self) -> Span {
         self.expression.full_span()
     }
+
+    /// the names of the additional arguments of the closure, which bind to
+    /// the fields of the same name. This is empty for function names and
+    /// for closures with only one argument. If the builder has a context,
+    /// the last argument is the context, which is not a binding.
+    pub fn bindings(&self, has_context: bool) -> Vec<&Ident> {
+        self.binding_arguments(has_context)
+            .into_iter()
+            .filter_map(|input| match input {
+                Pat::Ident(pat_ident) => Some(&pat_ident.ident),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// the arguments of the closure between the value itself and the context,
+    /// if there is one.
+    fn binding_arguments(&self, has_context: bool) -> Vec<&Pat> {
+        let InvariantExpression::Closure(ref closure) = self.expression else {
+            return Vec::new();
+        };
+        let mut arguments = closure.inputs.iter().skip(1).collect::<Vec<_>>();
+        if has_context {
+            arguments.pop();
+        }
+        arguments
+    }
+
+    /// a human readable description of the invariant for error messages, where
+    /// the subject is what the invariant is applied to, e.g. "field `x` of `Foo`".
+    pub fn describe(&self, subject: &str) -> String {
+        match self.expression {
+            InvariantExpression::Path(ref path) => {
+                let name = path.to_token_stream().to_string().replace(' ', "");
+                format!("invariant `{name}` of {subject} is violated")
+            }
+            InvariantExpression::Closure(_) => format!("invariant of {subject} is violated"),
+        }
+    }
+
+    /// the code of the invariant as written in the attribute, for the docs.
+    pub fn source(&self) -> String {
+        self.expression.to_token_stream().to_string()
+    }
+
+    /// whether the invariant is a `const fn` that can be evaluated in const
+    /// contexts.
+    pub fn is_const(&self) -> bool {
+        self.is_const
+    }
+
+    /// whether the invariant is only checked when `debug_assertions` are
+    /// enabled.
+    pub fn is_debug_only(&self) -> bool {
+        self.is_debug_only
+    }
+
+    /// whether the invariant is an async function, which must be awaited.
+    pub fn is_async(&self) -> bool {
+        self.is_async
+    }
 }
 
 impl InvariantAttribute {
@@ -86,10 +175,75 @@ impl InvariantAttribute {
             return Ok(None);
         };
 
-        let expression = InvariantExpression::try_from(&invariant_attr.meta)?;
+        Self::try_from(&invariant_attr.meta).map(Some)
+    }
+}
 
-        Ok(Some(Self { expression }))
+/// check that the additional arguments of the invariant closures are the names
+/// of fields which are declared before the field with the invariant. The
+/// invariant on the struct receives the whole struct, so it must not have
+/// additional arguments. If the builder has a context, the closures must take
+/// the context as their last argument.
+pub fn check_invariant_bindings(
+    fields: &[NamedField],
+    struct_invariant: Option<&InvariantAttribute>,
+    field_invariants: &[Option<InvariantAttribute>],
+    has_context: bool,
+) -> Result<(), CompileError> {
+    let all_invariants = || {
+        struct_invariant
+            .into_iter()
+            .chain(field_invariants.iter().flatten())
+    };
+    for invariant in all_invariants() {
+        let InvariantExpression::Closure(ref closure) = invariant.expression else {
+            continue;
+        };
+        if has_context && closure.inputs.len() < 2 {
+            return Err(CompileError::new_spanned(
+                closure,
+                "the builder has a validation context, so the closure must take it as its last argument, like in `|value, ctx| ...`",
+            ));
+        }
+        if let Some(binding) = invariant
+            .binding_arguments(has_context)
+            .into_iter()
+            .find(|input| !is_plain_binding(input))
+        {
+            return Err(CompileError::new_spanned(
+                binding,
+                "additional arguments of a validation closure must be the names of fields, like in `|height, max_height| ...`",
+            ));
+        }
+    }
+
+    if let Some(binding) = struct_invariant
+        .into_iter()
+        .flat_map(|invariant| invariant.bindings(has_context))
+        .next()
+    {
+        return Err(CompileError::new_spanned(
+            binding,
+            "the invariant of the struct receives the whole struct, additional arguments are only allowed for invariants on fields",
+        ));
+    }
+    for (index, (field, invariant)) in fields.iter().zip(field_invariants).enumerate() {
+        let Some(invariant) = invariant else {
+            continue;
+        };
+        for binding in invariant.bindings(has_context) {
+            if !fields[..index].iter().any(|f| f.ident == *binding) {
+                return Err(CompileError::new_spanned(
+                    binding,
+                    format!(
+                        "`{binding}` is not a field declared before `{}`, only those can be arguments of its invariant",
+                        field.ident
+                    ),
+                ));
+            }
+        }
     }
+    Ok(())
 }
 
 /// the expression inside the braces of the #[invariant(...)] attribute
@@ -121,7 +275,7 @@ impl ToTokens for InvariantExpression {
     }
 }
 
-impl TryFrom<&Meta> for InvariantExpression {
+impl TryFrom<&Meta> for InvariantAttribute {
     type Error = CompileError;
     fn try_from(meta: &Meta) -> Result<Self, CompileError> {
         match meta {
@@ -134,47 +288,54 @@ impl TryFrom<&Meta> for InvariantExpression {
                 "attribute requires closure or function name for validation in braces",
             )),
             Meta::List(list) => {
-                // first try parsing this as a path
-                if let Ok(path) = syn::parse::<Path>(list.tokens.clone().into()) {
-                    if !path.segments.is_empty() {
-                        return Ok(Self::Path(path));
+                let (expression, is_async, flags) =
+                    list.parse_args_with(parse_invariant_arguments)?;
+                let mut is_const = false;
+                let mut is_debug_only = false;
+                for flag in flags {
+                    let is_set = if flag == "const" {
+                        &mut is_const
+                    } else if flag == "debug_only" {
+                        &mut is_debug_only
                     } else {
                         return Err(CompileError::new_spanned(
-                            path,
-                            format!("attribute must have form #[{INVARIANT_ATTR}(expression)], where expression is a function name or closure"),
+                            &flag,
+                            format!("unknown flag `{flag}` for invariant, the allowed flags are `const` and `debug_only`"),
+                        ));
+                    };
+                    if *is_set {
+                        return Err(CompileError::new_spanned(
+                            flag,
+                            "flag must not be given more than once",
                         ));
                     }
+                    *is_set = true;
                 }
-                // otherwise this must be a closure
-                if let Ok(closure) = syn::parse::<ExprClosure>(list.tokens.clone().into()) {
-                    // we can do some error checks for better error messages.
-                    // We have no actual type information but we can make sure that
-                    // the closure is a single-argument closure that is not async
-                    if closure.asyncness.is_some() {
-                        Err(CompileError::new_spanned(
-                           closure.asyncness,
-                            "async in validation closure not allowed",
-                        ))
-                    } else if closure.capture.is_some() {
-                        Err(CompileError::new_spanned(
-                            closure.capture,
-                            "move capture in validation closure not allowed",
-                        ))
-                    } else if closure.inputs.len() != 1 {
-                        Err(CompileError::new_spanned(
+
+                // we can't call closures in const contexts (on stable), so
+                // const invariants must be given as paths to const functions.
+                if is_const {
+                    if let InvariantExpression::Closure(ref closure) = expression {
+                        return Err(CompileError::new_spanned(
                             closure,
-                            "validation closure must have exactly one argument",
-                        ))
-                    } else {
-                        check_closure(&closure)?;
-                        Ok(Self::Closure(closure))
+                            "const invariants must be given as the name of a `const fn`, closures are not allowed",
+                        ));
+                    }
+                    // and we can't await in const contexts either
+                    if is_async {
+                        return Err(CompileError::new_spanned(
+                            list,
+                            "invariants can't be both `async` and `const`",
+                        ));
                     }
-                } else {
-                    Err(CompileError::new_spanned(
-                        meta,
-                        format!("attribute must have form #[{INVARIANT_ATTR}(expression)], where expression is a function name or closure"),
-                    ))
                 }
+
+                Ok(Self {
+                    expression,
+                    is_const,
+                    is_debug_only,
+                    is_async,
+                })
             }
             Meta::NameValue(value) => Err(CompileError::new_spanned(
                 value,
@@ -184,6 +345,99 @@ impl TryFrom<&Meta> for InvariantExpression {
     }
 }
 
+/// parse the arguments inside the braces of the invariant attribute, which
+/// have the form `expression, flag1, flag2,...`, where the flags are optional
+/// identifiers (or keywords). The expression can be prefixed with `async` if
+/// it is a path, which is returned as the boolean.
+fn parse_invariant_arguments(
+    input: ParseStream,
+) -> syn::Result<(InvariantExpression, bool, Vec<Ident>)> {
+    // async closures start with `async` as well, but those are rejected
+    // when parsing the closure
+    let is_async = input.peek(Token![async])
+        && !input.peek2(Token![|])
+        && !input.peek2(Token![||])
+        && !input.peek2(Token![move]);
+    if is_async {
+        input.parse::<Token![async]>()?;
+    }
+    let expression = parse_invariant_expression(input)?;
+    let mut flags = Vec::new();
+    while !input.is_empty() {
+        input.parse::<Token![,]>()?;
+        if input.is_empty() {
+            // allow a trailing comma
+            break;
+        }
+        // we use parse_any, because flags like `const` are keywords
+        flags.push(input.call(Ident::parse_any)?);
+    }
+    Ok((expression, is_async, flags))
+}
+
+/// parse the expression part of the invariant attribute, which is either a path
+/// to a function or a closure.
+fn parse_invariant_expression(input: ParseStream) -> syn::Result<InvariantExpression> {
+    let malformed_error = || {
+        syn::Error::new(
+            input.span(),
+            format!("attribute must have form #[{INVARIANT_ATTR}(expression)], where expression is a function name or closure"),
+        )
+    };
+
+    // first try parsing this as a path
+    if input.fork().parse::<Path>().is_ok() {
+        let path: Path = input.parse()?;
+        // if the path is followed by anything other than a comma, this is
+        // not a function name but some other expression
+        if !input.is_empty() && !input.peek(Token![,]) {
+            return Err(malformed_error());
+        }
+        return Ok(InvariantExpression::Path(path));
+    }
+
+    // otherwise this must be a closure
+    let Ok(closure) = input.parse::<ExprClosure>() else {
+        return Err(malformed_error());
+    };
+
+    // we can do some error checks for better error messages.
+    // We have no actual type information but we can make sure that
+    // the closure is not async. The other arguments are checked later, when
+    // we know the fields and whether there is a context.
+    if closure.asyncness.is_some() {
+        Err(syn::Error::new_spanned(
+            closure.asyncness,
+            "async validation closures are not allowed, use the name of an async function instead, like in `#[invariant(async my_function)]`",
+        ))
+    } else if closure.capture.is_some() {
+        Err(syn::Error::new_spanned(
+            closure.capture,
+            "move capture in validation closure not allowed",
+        ))
+    } else if closure.inputs.is_empty() {
+        Err(syn::Error::new_spanned(
+            closure,
+            "validation closure must have at least one argument",
+        ))
+    } else {
+        check_closure(&closure)?;
+        Ok(InvariantExpression::Closure(closure))
+    }
+}
+
+/// whether the pattern is a plain name without `ref`, `mut` or subpatterns.
+fn is_plain_binding(pattern: &Pat) -> bool {
+    match pattern {
+        Pat::Ident(pat_ident) => {
+            pat_ident.by_ref.is_none()
+                && pat_ident.mutability.is_none()
+                && pat_ident.subpat.is_none()
+        }
+        _ => false,
+    }
+}
+
 /// a helper function that makes sure that the closure does not use
 /// self or the identifier that we use for the finished instance in the builder
 /// in its body.
@@ -0,0 +1,153 @@
+use std::future::Future;
+use std::pin::pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+use quick_builder_derive::QuickBuilder;
+
+/// a minimal executor, so we don't depend on a runtime for the tests
+fn block_on<F: Future>(future: F) -> F::Output {
+    struct NoopWaker;
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+/// a future that is pending once before it resolves, like a lookup that
+/// has to wait for I/O
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+    fn poll(mut self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+async fn is_known_id(id: &u32) -> bool {
+    YieldOnce(false).await;
+    [1, 2, 3].contains(id)
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(dyn_builder)]
+#[invariant(async is_consistent)]
+struct Order {
+    #[invariant(async is_known_id)]
+    customer_id: u32,
+    #[invariant(|amount| *amount > 0)]
+    amount: u32,
+}
+
+async fn is_consistent(order: &Order) -> bool {
+    YieldOnce(false).await;
+    order.customer_id != 3 || order.amount <= 100
+}
+
+#[test]
+fn async_invariants_are_awaited_in_build() {
+    let order = block_on(Order::builder().customer_id(1).amount(10).build());
+    assert_eq!(
+        order,
+        Some(Order {
+            customer_id: 1,
+            amount: 10
+        })
+    );
+    assert!(block_on(Order::builder().customer_id(4).amount(10).build()).is_none());
+    assert!(block_on(Order::builder().customer_id(3).amount(1000).build()).is_none());
+    assert!(block_on(Order::builder().customer_id(1).amount(0).build()).is_none());
+}
+
+#[test]
+fn build_expect_is_async_as_well() {
+    let order = block_on(
+        Order::builder()
+            .customer_id(3)
+            .amount(100)
+            .build_expect("valid"),
+    );
+    assert_eq!(order.amount, 100);
+}
+
+#[test]
+#[should_panic(expected = "invariant `is_known_id` of field `customer_id` of `Order` is violated")]
+fn build_expect_panics_for_violated_async_invariant() {
+    block_on(
+        Order::builder()
+            .customer_id(5)
+            .amount(1)
+            .build_expect("invalid"),
+    );
+}
+
+#[test]
+fn dyn_builder_awaits_async_invariants() {
+    let mut builder = Order::dyn_builder();
+    builder.customer_id(4).amount(10);
+    assert!(block_on(builder.try_build()).is_err());
+    let mut builder = Order::dyn_builder();
+    builder.customer_id(2).amount(10);
+    assert!(block_on(builder.try_build()).is_ok());
+}
+
+static ASYNC_CHECKS: AtomicUsize = AtomicUsize::new(0);
+
+async fn is_counted(_: &i32) -> bool {
+    ASYNC_CHECKS.fetch_add(1, Ordering::SeqCst);
+    true
+}
+
+#[derive(Debug, QuickBuilder)]
+struct Counted {
+    #[invariant(async is_counted)]
+    value: i32,
+    #[invariant(|flag| *flag)]
+    flag: bool,
+}
+
+#[test]
+fn synchronous_invariants_are_checked_first() {
+    assert!(block_on(Counted::builder().value(1).flag(false).build()).is_none());
+    assert_eq!(ASYNC_CHECKS.load(Ordering::SeqCst), 0);
+    assert!(block_on(Counted::builder().value(1).flag(true).build()).is_some());
+    assert_eq!(ASYNC_CHECKS.load(Ordering::SeqCst), 1);
+}
+
+struct Store {
+    ids: Vec<u32>,
+}
+
+async fn exists_in_store(id: &u32, store: &Store) -> bool {
+    YieldOnce(false).await;
+    store.ids.contains(id)
+}
+
+#[derive(Debug, QuickBuilder)]
+#[quick_builder(context = Store)]
+struct Reference {
+    #[invariant(async exists_in_store)]
+    id: u32,
+}
+
+#[test]
+fn async_invariants_get_the_context() {
+    let store = Store { ids: vec![7, 8] };
+    assert!(block_on(Reference::builder().id(7).build_with(&store)).is_some());
+    assert!(block_on(Reference::builder().id(9).build_with(&store)).is_none());
+}
@@ -0,0 +1,81 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[invariant(Interval::is_valid)]
+struct Interval {
+    #[invariant(|lower| lower.is_finite())]
+    lower: f64,
+    #[invariant(checks::is_finite)]
+    upper: f64,
+}
+
+impl Interval {
+    fn is_valid(&self) -> bool {
+        self.lower <= self.upper
+    }
+}
+
+mod checks {
+    pub fn is_finite(value: &f64) -> bool {
+        value.is_finite()
+    }
+}
+
+#[test]
+fn build_expect_returns_the_valid_instance() {
+    let interval = Interval::builder()
+        .lower(0.)
+        .upper(1.)
+        .build_expect("interval should be valid");
+    assert_eq!(
+        interval,
+        Interval {
+            lower: 0.,
+            upper: 1.
+        }
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "interval should be valid: invariant of field `lower` of `Interval` is violated"
+)]
+fn build_expect_names_the_violated_field_invariant() {
+    let _ = Interval::builder()
+        .lower(f64::NAN)
+        .upper(1.)
+        .build_expect("interval should be valid");
+}
+
+#[test]
+#[should_panic(
+    expected = "interval should be valid: invariant `checks::is_finite` of field `upper` of `Interval` is violated"
+)]
+fn build_expect_names_the_violated_function() {
+    let _ = Interval::builder()
+        .lower(0.)
+        .upper(f64::INFINITY)
+        .build_expect("interval should be valid");
+}
+
+#[test]
+#[should_panic(expected = "invariant `Interval::is_valid` of `Interval` is violated")]
+fn build_expect_names_the_violated_struct_invariant() {
+    let _ = Interval::builder()
+        .lower(1.)
+        .upper(0.)
+        .build_expect("interval should be valid");
+}
+
+#[test]
+fn build_unchecked_skips_the_invariants() {
+    // Safety: the invariants are violated on purpose, but nothing relies on them
+    let interval = unsafe { Interval::builder().lower(1.).upper(0.).build_unchecked() };
+    assert_eq!(
+        interval,
+        Interval {
+            lower: 1.,
+            upper: 0.
+        }
+    );
+}
@@ -0,0 +1,89 @@
+use quick_builder_derive::QuickBuilder;
+use std::collections::HashSet;
+
+#[derive(PartialEq, Debug, QuickBuilder)]
+#[quick_builder(derive(Clone, Debug, PartialEq, Eq, Hash))]
+#[invariant(|this| this.width * this.height == this.data.len())]
+struct Image<T> {
+    width: usize,
+    height: usize,
+    data: Vec<T>,
+}
+
+// this type is not Clone or Debug, but the builder can still be cloned and
+// printed as long as this field has not been set
+#[derive(PartialEq)]
+struct NotCloneOrDebug;
+
+#[derive(QuickBuilder)]
+#[quick_builder(derive(Clone, Copy, Debug))]
+struct WithLifetimes<'a, T> {
+    name: &'a str,
+    value: T,
+    last: NotCloneOrDebug,
+}
+
+#[test]
+fn partial_builders_can_be_cloned_as_templates() {
+    let template = Image::builder().width(2).height(1);
+    let first = template.clone().data(vec![1, 2]).build();
+    let second = template.data(vec![3, 4]).build();
+    assert_eq!(
+        first,
+        Some(Image {
+            width: 2,
+            height: 1,
+            data: vec![1, 2]
+        })
+    );
+    assert_eq!(
+        second,
+        Some(Image {
+            width: 2,
+            height: 1,
+            data: vec![3, 4]
+        })
+    );
+}
+
+#[test]
+fn builders_print_the_fields_set_so_far() {
+    let builder = Image::<f32>::builder();
+    assert_eq!(format!("{builder:?}"), "ImageBuilder { .. }");
+    let builder = builder.width(2).height(1);
+    assert_eq!(
+        format!("{builder:?}"),
+        "ImageBuilder { width: 2, height: 1, .. }"
+    );
+    let builder = builder.data(vec![1., 2.]);
+    assert_eq!(
+        format!("{builder:?}"),
+        "ImageBuilder { width: 2, height: 1, data: [1.0, 2.0] }"
+    );
+}
+
+#[test]
+fn builders_can_be_compared_and_hashed() {
+    let first = Image::<u8>::builder().width(2);
+    let second = Image::<u8>::builder().width(2);
+    let third = Image::<u8>::builder().width(3);
+    assert_eq!(first, second);
+    assert_ne!(first, third);
+    let set: HashSet<_> = [first, second, third].into_iter().collect();
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn bounds_only_apply_to_fields_that_have_been_set() {
+    let name = String::from("name");
+    let builder = WithLifetimes::<NotCloneOrDebug>::builder().name(&name);
+    let copied = builder;
+    assert_eq!(
+        format!("{builder:?}"),
+        "WithLifetimesBuilder { name: \"name\", .. }"
+    );
+    let built = copied.value(NotCloneOrDebug).last(NotCloneOrDebug).build();
+    assert!(
+        built.name == "name" && built.value == NotCloneOrDebug && built.last == NotCloneOrDebug
+    );
+}
@@ -0,0 +1,48 @@
+use quick_builder_derive::QuickBuilder;
+
+mod shapes {
+    use quick_builder_derive::QuickBuilder;
+
+    #[derive(Debug, PartialEq, QuickBuilder)]
+    pub struct Circle {
+        pub radius: f32,
+    }
+
+    #[derive(Debug, PartialEq, QuickBuilder)]
+    pub struct Square<'a> {
+        pub name: &'a str,
+        pub side: f32,
+    }
+}
+
+// the builder of a private struct is private as well
+#[derive(Debug, PartialEq, QuickBuilder)]
+struct Private {
+    value: i32,
+}
+
+fn with_radius(builder: shapes::CircleBuilder<()>) -> shapes::CircleBuilder<(f32,)> {
+    builder.radius(1.)
+}
+
+fn with_name<'a>(builder: shapes::SquareBuilder<'a, ()>) -> shapes::SquareBuilder<'a, (&'a str,)> {
+    builder.name("square")
+}
+
+#[test]
+fn builders_are_declared_next_to_the_struct() {
+    let circle = with_radius(shapes::Circle::builder()).build();
+    assert_eq!(circle, shapes::Circle { radius: 1. });
+
+    let square = with_name(shapes::SquareBuilder::new()).side(2.).build();
+    assert_eq!(
+        square,
+        shapes::Square {
+            name: "square",
+            side: 2.
+        }
+    );
+
+    let private = PrivateBuilder::new().value(1).build();
+    assert_eq!(private, Private { value: 1 });
+}
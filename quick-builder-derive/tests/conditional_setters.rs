@@ -0,0 +1,66 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+struct Window {
+    width: u32,
+    height: u32,
+    title: String,
+}
+
+// a field called apply must not clash with the apply function
+#[derive(Debug, PartialEq, QuickBuilder)]
+struct Settings {
+    apply: bool,
+    value: i32,
+}
+
+const SMALL_WIDTH: WindowSize = WindowSize::builder()
+    .width_if(true, 320, 640)
+    .height(240)
+    .build();
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+struct WindowSize {
+    width: u32,
+    height: u32,
+}
+
+#[test]
+fn conditional_setters_pick_one_of_two_values() {
+    for fullscreen in [true, false] {
+        let window = Window::builder()
+            .width_if(fullscreen, 1920, 640)
+            .height(480)
+            .title("main".into())
+            .build();
+        assert_eq!(window.width, if fullscreen { 1920 } else { 640 });
+    }
+    assert_eq!(SMALL_WIDTH.width, 320);
+}
+
+#[test]
+fn apply_allows_conditional_setup_in_one_chain() {
+    let title = Some("custom");
+    let window = Window::builder()
+        .width(640)
+        .height(480)
+        .apply(|builder| match title {
+            Some(title) => builder.title(title.into()),
+            None => builder.title("default".into()),
+        })
+        .build();
+    assert_eq!(
+        window,
+        Window {
+            width: 640,
+            height: 480,
+            title: "custom".into()
+        }
+    );
+}
+
+#[test]
+fn fields_can_be_named_like_the_additional_functions() {
+    let settings = Settings::builder().apply(true).value(1).build();
+    assert!(settings.apply);
+}
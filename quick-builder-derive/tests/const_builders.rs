@@ -0,0 +1,157 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(PartialEq, Debug, QuickBuilder)]
+struct WithoutInvariants<'a> {
+    first: i32,
+    second: &'a str,
+}
+
+#[derive(PartialEq, Debug, QuickBuilder)]
+#[invariant(checks::start_before_end, const)]
+struct WithConstInvariants {
+    #[invariant(checks::is_positive, const)]
+    start: i32,
+    end: i32,
+}
+
+#[derive(PartialEq, Debug, QuickBuilder)]
+struct WithGenericsAndConstInvariants<'a, T: Copy> {
+    #[invariant(checks::is_positive, const)]
+    count: i32,
+    value: &'a T,
+}
+
+mod checks {
+    use super::WithConstInvariants;
+
+    pub const fn is_positive(value: &i32) -> bool {
+        *value > 0
+    }
+
+    pub const fn start_before_end(this: &WithConstInvariants) -> bool {
+        this.start < this.end
+    }
+}
+
+const WITHOUT_INVARIANTS: WithoutInvariants<'static> =
+    WithoutInvariants::builder().first(1).second("one").build();
+
+const WITH_CONST_INVARIANTS: WithConstInvariants =
+    match WithConstInvariants::builder().start(1).end(10).build() {
+        Some(value) => value,
+        None => panic!("invariant violated"),
+    };
+
+static WITH_GENERICS: Option<WithGenericsAndConstInvariants<'static, f32>> =
+    WithGenericsAndConstInvariants::builder()
+        .count(-1)
+        .value(&1.)
+        .build();
+
+#[derive(PartialEq, Debug, QuickBuilder)]
+struct WithRuntimeInvariants {
+    #[invariant(|value| *value > 0)]
+    value: i32,
+}
+
+// Safety: the invariant holds, it just can't be checked at compile time
+const WITH_RUNTIME_INVARIANTS: WithRuntimeInvariants =
+    unsafe { WithRuntimeInvariants::builder().value(1).build_unchecked() };
+
+#[test]
+fn unchecked_builds_are_const_even_with_runtime_invariants() {
+    assert_eq!(WITH_RUNTIME_INVARIANTS, WithRuntimeInvariants { value: 1 });
+}
+
+#[test]
+fn builders_can_be_used_in_const_contexts() {
+    assert_eq!(
+        WITHOUT_INVARIANTS,
+        WithoutInvariants {
+            first: 1,
+            second: "one"
+        }
+    );
+    assert_eq!(
+        WITH_CONST_INVARIANTS,
+        WithConstInvariants { start: 1, end: 10 }
+    );
+    assert_eq!(WITH_GENERICS, None);
+}
+
+#[derive(PartialEq, Debug, QuickBuilder)]
+struct WithDropGlue<T> {
+    count: i32,
+    value: T,
+    name: String,
+}
+
+// the setters can only be const as long as the fields set before don't have
+// drop glue.
+const PARTIAL_BUILDER_WITH_DROP_GLUE: WithDropGlueBuilder<Vec<f32>, (i32, Vec<f32>)> =
+    WithDropGlue::builder().count(1).value(Vec::new());
+
+// a user type that shadows `Option`, but has drop glue. The build function
+// drops the instance if the invariant is violated, so it can't be const here.
+mod shadowing {
+    #[derive(PartialEq, Debug)]
+    pub struct Option<T>(pub T, pub String);
+}
+
+#[derive(PartialEq, Debug, QuickBuilder)]
+struct WithOptions {
+    first: ::core::option::Option<std::primitive::i32>,
+    second: std::option::Option<u8>,
+    shadowed: shadowing::Option<i32>,
+    #[invariant(checks::is_positive, const)]
+    last: i32,
+}
+
+const PARTIAL_BUILDER_WITH_OPTIONS: WithOptionsBuilder<(
+    Option<i32>,
+    Option<u8>,
+    shadowing::Option<i32>,
+)> = WithOptions::builder()
+    .first(Some(1))
+    .second(None)
+    .shadowed(shadowing::Option(2, String::new()));
+
+#[test]
+fn const_invariants_are_also_checked_at_runtime() {
+    assert_eq!(
+        WithConstInvariants::builder().start(-1).end(10).build(),
+        None
+    );
+    assert_eq!(WithConstInvariants::builder().start(2).end(1).build(), None);
+    assert_eq!(
+        WithConstInvariants::builder().start(1).end(2).build(),
+        Some(WithConstInvariants { start: 1, end: 2 })
+    );
+}
+
+#[test]
+fn builders_with_drop_glue_are_const_as_far_as_possible() {
+    let built = PARTIAL_BUILDER_WITH_DROP_GLUE.name("name".into()).build();
+    assert_eq!(
+        built,
+        WithDropGlue {
+            count: 1,
+            value: vec![],
+            name: "name".into()
+        }
+    );
+}
+
+#[test]
+fn types_that_shadow_std_types_are_not_const() {
+    let built = PARTIAL_BUILDER_WITH_OPTIONS.last(3).build();
+    assert_eq!(
+        built,
+        Some(WithOptions {
+            first: Some(1),
+            second: None,
+            shadowed: shadowing::Option(2, String::new()),
+            last: 3
+        })
+    );
+}
@@ -0,0 +1,47 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+struct SortedVec {
+    #[invariant(|v| v.windows(2).all(|w| w[0] <= w[1]), debug_only)]
+    values: Vec<i32>,
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[invariant(has_valid_checksum, debug_only)]
+struct Packet {
+    #[invariant(|payload| !payload.is_empty())]
+    payload: Vec<u8>,
+    checksum: u8,
+}
+
+fn has_valid_checksum(packet: &Packet) -> bool {
+    packet
+        .payload
+        .iter()
+        .fold(0u8, |acc, b| acc.wrapping_add(*b))
+        == packet.checksum
+}
+
+#[test]
+fn debug_only_invariants_depend_on_debug_assertions() {
+    let unsorted = SortedVec::builder().values(vec![2, 1]).build();
+    if cfg!(debug_assertions) {
+        assert_eq!(unsorted, None);
+    } else {
+        assert!(unsorted.is_some());
+    }
+    let sorted = SortedVec::builder().values(vec![1, 2]).build();
+    assert_eq!(sorted, Some(SortedVec { values: vec![1, 2] }));
+}
+
+#[test]
+fn other_invariants_are_always_checked() {
+    let empty = Packet::builder().payload(vec![]).checksum(0).build();
+    assert_eq!(empty, None);
+
+    let wrong_checksum = Packet::builder().payload(vec![1, 2]).checksum(0).build();
+    assert_eq!(wrong_checksum.is_none(), cfg!(debug_assertions));
+
+    let valid = Packet::builder().payload(vec![1, 2]).checksum(3).build();
+    assert!(valid.is_some());
+}
@@ -0,0 +1,9 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+struct Foo {
+    #[invariant(async |x| *x > 0)]
+    x: i32,
+}
+
+fn main() {}
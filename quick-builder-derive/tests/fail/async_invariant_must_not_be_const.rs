@@ -0,0 +1,13 @@
+use quick_builder_derive::QuickBuilder;
+
+const fn is_positive(x: &i32) -> bool {
+    *x > 0
+}
+
+#[derive(QuickBuilder)]
+struct Foo {
+    #[invariant(async is_positive, const)]
+    x: i32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+#[quick_builder(derive(Copy, Debug))]
+struct Foo {
+    first: i32,
+}
+
+fn main() {}
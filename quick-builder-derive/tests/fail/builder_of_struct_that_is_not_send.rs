@@ -0,0 +1,16 @@
+use quick_builder_derive::QuickBuilder;
+use std::rc::Rc;
+
+#[derive(QuickBuilder)]
+struct Counter {
+    count: Rc<usize>,
+    step: usize,
+}
+
+fn assert_send<T: Send>(_: &T) {}
+
+fn main() {
+    // the builder is not Send, even before the Rc is set
+    let builder = Counter::builder();
+    assert_send(&builder);
+}
@@ -0,0 +1,9 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+struct Foo {
+    #[invariant(|x| *x > 0, const)]
+    x: i32,
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+struct Foo {
+    #[invariant(is_positive, const)]
+    x: i32,
+}
+
+const fn is_positive(x: &i32) -> bool {
+    *x > 0
+}
+
+const FOO: Foo = match Foo::builder().x(-1).build() {
+    Some(foo) => foo,
+    None => panic!("invariant violated"),
+};
+
+fn main() {
+    let _ = FOO.x;
+}
@@ -0,0 +1,12 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+#[quick_builder(context = u32)]
+struct Foo {
+    #[invariant(|value, max| value <= max)]
+    value: u32,
+}
+
+fn main() {
+    let _ = Foo::builder().value(1).build();
+}
@@ -0,0 +1,10 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+#[quick_builder(context = u32)]
+struct Foo {
+    #[invariant(|value| *value > 0)]
+    value: u32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+#[quick_builder(context = u32)]
+struct Foo {
+    value: u32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+#[invariant(|this, height| this.max_height >= *height)]
+struct Foo {
+    height: u32,
+    max_height: u32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+struct Foo {
+    #[invariant(|h, max_height| h <= max_height)]
+    height: u32,
+    max_height: u32,
+}
+
+fn main() {}
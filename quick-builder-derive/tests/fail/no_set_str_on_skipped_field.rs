@@ -0,0 +1,11 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+#[quick_builder(dyn_builder, set_str)]
+struct Foo {
+    first: i32,
+    #[builder(skip = first * 2, no_set_str)]
+    second: i32,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use quick_builder_derive::QuickBuilder;
+
+// the tests use quick-builder with its default features, so `proptest` is disabled
+#[derive(QuickBuilder)]
+#[quick_builder(proptest)]
+struct Foo {
+    #[invariant(|value| *value > 0)]
+    value: i32,
+}
+
+fn main() {}
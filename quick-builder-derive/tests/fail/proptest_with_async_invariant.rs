@@ -0,0 +1,14 @@
+use quick_builder_derive::QuickBuilder;
+
+async fn is_positive(x: &i32) -> bool {
+    *x > 0
+}
+
+#[derive(Debug, QuickBuilder)]
+#[quick_builder(proptest)]
+struct Foo {
+    #[invariant(async is_positive)]
+    x: i32,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use quick_builder_derive::QuickBuilder;
+
+struct Limits {
+    max: u32,
+}
+
+#[derive(Debug, QuickBuilder)]
+#[quick_builder(proptest, context = Limits)]
+struct Foo {
+    #[invariant(|value, limits| *value <= limits.max)]
+    value: u32,
+}
+
+fn main() {}
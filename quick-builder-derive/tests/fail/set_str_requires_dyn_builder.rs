@@ -0,0 +1,9 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+#[quick_builder(set_str)]
+struct Foo {
+    first: i32,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder, PartialEq, Debug)]
+struct Foo<T> {
+    first: i32,
+    second: T,
+    third: f32,
+}
+
+fn main() {
+    // this should fail because second(...) must be called before third(...)
+    let _built = Foo::builder().first(1).third(0.3).build();
+}
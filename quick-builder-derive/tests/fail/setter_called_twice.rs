@@ -0,0 +1,12 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder, PartialEq, Debug)]
+struct Foo {
+    first: i32,
+    second: f32,
+}
+
+fn main() {
+    // this should fail because first(...) was already called
+    let _built = Foo::builder().first(1).first(2).second(0.3).build();
+}
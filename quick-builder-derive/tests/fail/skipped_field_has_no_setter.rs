@@ -0,0 +1,12 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+struct Foo {
+    first: Vec<i32>,
+    #[builder(skip = first.len())]
+    len: usize,
+}
+
+fn main() {
+    let _ = Foo::builder().first(vec![1, 2]).len(2).build();
+}
@@ -0,0 +1,10 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+#[quick_builder(stage_aliases)]
+struct Ambiguous {
+    len: usize,
+    _len: usize,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+struct Foo {
+    first: i32,
+    #[builder(skip = first * 2, strategy = 0..10)]
+    second: i32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+struct Foo {
+    first: Vec<i32>,
+    #[builder(skip = first.len(), transform = |len| len + 1)]
+    len: usize,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+struct Foo {
+    #[builder(default = 1)]
+    first: i32,
+}
+
+fn main() {}
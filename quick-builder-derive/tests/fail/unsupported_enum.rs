@@ -0,0 +1,9 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+enum Foo {
+    First(i32),
+    Second { x: f32 },
+}
+
+fn main() {}
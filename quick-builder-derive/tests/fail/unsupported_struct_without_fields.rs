@@ -0,0 +1,6 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+struct Foo {}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(QuickBuilder)]
+union Foo {
+    first: i32,
+    second: f32,
+}
+
+fn main() {}
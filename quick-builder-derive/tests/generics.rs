@@ -0,0 +1,99 @@
+use quick_builder_derive::QuickBuilder;
+use std::fmt::Debug;
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(derive(Clone, Debug))]
+struct WithDefaults<'a, T: Clone = u32, const N: usize = 2> {
+    label: &'a str,
+    #[invariant(|values| values.len() <= N)]
+    values: Vec<T>,
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[invariant(|m| m.data.len() == R * C)]
+struct Matrix<const R: usize, const C: usize> {
+    #[invariant(|data| data.iter().all(|x| x.is_finite()))]
+    data: Vec<f64>,
+    #[builder(skip = R == C)]
+    is_square: bool,
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+struct FixedSize<T: Copy, const N: usize> {
+    #[invariant(|values| values.len() == N)]
+    values: [T; N],
+}
+
+#[derive(QuickBuilder)]
+struct Unsized<'a, T: ?Sized + Debug, U>
+where
+    U: AsRef<T> + ?Sized,
+{
+    reference: &'a T,
+    #[invariant(|boxed| !format!("{boxed:?}").is_empty())]
+    boxed: Box<T>,
+    other: &'a U,
+}
+
+#[test]
+fn builders_support_generic_defaults() {
+    let built: Option<WithDefaults> = WithDefaults::builder()
+        .label("label")
+        .values(vec![1, 2])
+        .build();
+    assert_eq!(
+        built,
+        Some(WithDefaults {
+            label: "label",
+            values: vec![1, 2]
+        })
+    );
+
+    let too_many: Option<WithDefaults> = WithDefaults::builder()
+        .label("label")
+        .values(vec![1, 2, 3])
+        .build();
+    assert_eq!(too_many, None);
+
+    let three = WithDefaults::<'_, f32, 3>::builder()
+        .label("label")
+        .values(vec![1., 2., 3.]);
+    assert!(three.clone().build().is_some());
+}
+
+#[test]
+fn invariants_can_use_const_generics() {
+    let matrix = Matrix::<2, 3>::builder().data(vec![0.; 6]).build();
+    assert_eq!(
+        matrix,
+        Some(Matrix {
+            data: vec![0.; 6],
+            is_square: false
+        })
+    );
+    assert!(Matrix::<2, 2>::builder()
+        .data(vec![0.; 6])
+        .build()
+        .is_none());
+    assert!(Matrix::<1, 1>::builder()
+        .data(vec![f64::NAN])
+        .build()
+        .is_none());
+
+    let fixed = FixedSize::builder().values([1, 2, 3]).build();
+    assert_eq!(fixed, Some(FixedSize { values: [1, 2, 3] }));
+}
+
+#[test]
+fn builders_support_unsized_generics() {
+    let text = String::from("text");
+    let built = Unsized::<str, String>::builder()
+        .reference("reference")
+        .boxed("boxed".into())
+        .other(&text)
+        .build();
+    let built = built.expect("invariants should hold");
+    assert_eq!(built.reference, "reference");
+    assert_eq!(&*built.boxed, "boxed");
+    assert_eq!(built.other, "text");
+}
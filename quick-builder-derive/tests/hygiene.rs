@@ -0,0 +1,147 @@
+use quick_builder_derive::QuickBuilder;
+
+// items with the same names as the std types and the local variables of the
+// generated code must not change how the generated code behaves.
+#[allow(dead_code)]
+struct Option;
+#[allow(dead_code)]
+struct Result;
+#[allow(dead_code)]
+struct PhantomData;
+#[allow(non_upper_case_globals)]
+const state: i32 = 100;
+#[allow(non_upper_case_globals)]
+const description: &str = "not the description";
+#[allow(non_upper_case_globals)]
+const invalid: i32 = 7;
+#[allow(non_upper_case_globals)]
+const finished: i32 = 3;
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(dyn_builder, set_str)]
+#[invariant(|limited| limited.current <= state)]
+struct Limited {
+    #[builder(transform = |current: i32| current.min(state))]
+    #[invariant(|current| *current != invalid)]
+    current: i32,
+    #[invariant(|offset| *offset < finished)]
+    offset: i32,
+}
+
+#[test]
+fn local_names_of_the_generated_code_are_hidden() {
+    let limited = Limited::builder().current(1000).offset(1).build();
+    assert_eq!(
+        limited,
+        Some(Limited {
+            current: 100,
+            offset: 1
+        })
+    );
+    assert_eq!(Limited::builder().current(7).offset(1).build(), None);
+    assert_eq!(Limited::builder().current(1).offset(3).build(), None);
+
+    let mut builder = Limited::dyn_builder();
+    builder.set_str("current", "1000").unwrap();
+    builder.set_str("offset", "2").unwrap();
+    assert_eq!(builder.try_build().map(|l| l.current), Ok(100));
+    assert_eq!(description, "not the description");
+}
+
+// two structs in the same module with the same field names must not clash
+#[derive(QuickBuilder)]
+struct First {
+    x: i32,
+}
+
+#[derive(QuickBuilder)]
+struct Second {
+    x: i32,
+}
+
+#[test]
+fn structs_with_the_same_fields_in_one_module() {
+    assert_eq!(First::builder().x(1).build().x, 1);
+    assert_eq!(Second::builder().x(2).build().x, 2);
+}
+
+// fields with the names of the methods of the builder must not clash with the
+// hidden methods that are only there for the error messages
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[invariant(|job| job.build > 0)]
+struct Job {
+    name: &'static str,
+    build: u32,
+    new: bool,
+    build_unchecked: bool,
+}
+
+#[test]
+fn fields_with_the_names_of_builder_methods() {
+    let job = Job::builder()
+        .name("nightly")
+        .build(1)
+        .new(true)
+        .build_unchecked(false)
+        .build();
+    assert_eq!(
+        job,
+        Some(Job {
+            name: "nightly",
+            build: 1,
+            new: true,
+            build_unchecked: false
+        })
+    );
+    let job = Job::builder()
+        .name("nightly")
+        .build(0)
+        .new(true)
+        .build_unchecked(false);
+    assert_eq!(job.build(), None);
+}
+
+#[test]
+fn structs_inside_functions() {
+    trait Positive {
+        fn is_positive(&self) -> bool;
+    }
+
+    impl Positive for i32 {
+        fn is_positive(&self) -> bool {
+            *self > 0
+        }
+    }
+
+    fn is_ordered(range: &Range) -> bool {
+        range.start <= range.end
+    }
+
+    #[derive(Debug, PartialEq, QuickBuilder)]
+    #[quick_builder(derive(Clone, Debug), dyn_builder, stage_aliases)]
+    #[invariant(is_ordered)]
+    struct Range {
+        #[invariant(Positive::is_positive)]
+        start: i32,
+        end: i32,
+    }
+
+    let partial: RangeBuilderAfterStart = Range::builder().start(1);
+    assert_eq!(
+        partial.clone().end(2).build(),
+        Some(Range { start: 1, end: 2 })
+    );
+    assert_eq!(partial.end(0).build(), None);
+    assert_eq!(Range::builder().start(0).end(2).build(), None);
+
+    let mut builder = RangeDynBuilder::new();
+    builder.start(1).end(5);
+    assert!(builder.try_build().is_ok());
+
+    #[derive(Debug, QuickBuilder)]
+    struct Wrapper<'a, T> {
+        inner: &'a T,
+    }
+    let inner = 5;
+    assert_eq!(*Wrapper::builder().inner(&inner).build().inner, 5);
+}
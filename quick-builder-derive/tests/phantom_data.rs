@@ -0,0 +1,119 @@
+use quick_builder_derive::QuickBuilder;
+use std::marker::PhantomData;
+
+struct Meters;
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+struct Length<Unit> {
+    value: f64,
+    unit: PhantomData<Unit>,
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+struct Id<'a, T> {
+    #[invariant(|id| *id > 0)]
+    id: u64,
+    marker: PhantomData<&'a T>,
+}
+
+// the raw pointer makes the struct neither Send nor Sync, which we override
+#[derive(QuickBuilder)]
+struct Handle {
+    len: usize,
+    ptr: *const u8,
+}
+
+unsafe impl Send for Handle {}
+
+fn assert_send<T: Send>(_: &T) {}
+
+#[allow(dead_code)]
+// this only compiles if the builder is covariant in the lifetime, like the struct
+fn shorten<'short>(builder: IdBuilder<'static, u8, ()>) -> IdBuilder<'short, u8, ()> {
+    builder
+}
+
+#[allow(dead_code)]
+// the same must hold once fields have been set
+fn shorten_filled<'short>(
+    builder: IdBuilder<'static, u8, (u64,)>,
+) -> IdBuilder<'short, u8, (u64,)> {
+    builder
+}
+
+// only the PhantomData of the standard library is skipped, not a user type
+// with the same name
+mod tags {
+    #[derive(Debug, PartialEq)]
+    pub struct PhantomData(pub u8);
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+struct Tagged<T> {
+    tag: tags::PhantomData,
+    marker: ::core::marker::PhantomData<T>,
+}
+
+#[derive(QuickBuilder)]
+struct Shared<'a> {
+    name: &'a str,
+    count: std::sync::Arc<usize>,
+}
+
+fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+const CONST_LENGTH: Length<Meters> = Length::builder().value(1.).build();
+
+#[test]
+fn phantom_data_fields_are_skipped() {
+    let length = Length::<Meters>::builder().value(2.).build();
+    assert_eq!(length.value, 2.);
+    assert_eq!(CONST_LENGTH.value, 1.);
+
+    let id = Id::<String>::builder().id(1).build();
+    assert_eq!(
+        id,
+        Some(Id {
+            id: 1,
+            marker: PhantomData
+        })
+    );
+    assert_eq!(Id::<String>::builder().id(0).build(), None);
+}
+
+#[test]
+fn types_named_phantom_data_are_not_skipped() {
+    let tagged = Tagged::<u8>::builder().tag(tags::PhantomData(1)).build();
+    assert_eq!(
+        tagged,
+        Tagged {
+            tag: tags::PhantomData(1),
+            marker: PhantomData
+        }
+    );
+}
+
+#[test]
+fn builders_have_the_auto_traits_of_the_struct() {
+    let builder = Handle::builder();
+    assert_send(&builder);
+    let builder = builder.len(0);
+    assert_send(&builder);
+    // the builder stays Send after the pointer is set, like the struct
+    let builder = builder.ptr(std::ptr::null());
+    assert_send(&builder);
+    let handle = builder.build();
+    assert_send(&handle);
+    assert_eq!(handle.len, 0);
+    assert!(handle.ptr.is_null());
+
+    let builder = Shared::builder();
+    assert_send_sync(&builder);
+    let builder = builder.name("shared");
+    assert_send_sync(&builder);
+    let builder = builder.count(std::sync::Arc::new(1));
+    assert_send_sync(&builder);
+    let shared = builder.build();
+    assert_eq!(shared.name, "shared");
+    assert_eq!(*shared.count, 1);
+}
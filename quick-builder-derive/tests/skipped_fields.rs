@@ -0,0 +1,52 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[invariant(|rect| rect.area > 0)]
+struct Rectangle {
+    width: u32,
+    #[builder(skip = 0)]
+    revision: u32,
+    height: u32,
+    #[builder(skip = width * height)]
+    area: u32,
+}
+
+#[derive(QuickBuilder)]
+struct Polyline<T> {
+    // skipped fields can refer to skipped fields declared before them
+    #[builder(skip = points.len())]
+    len: usize,
+    #[builder(skip = len < 2)]
+    is_degenerate: bool,
+    points: Vec<T>,
+    #[builder(skip = format!("polyline with {len} points"))]
+    description: String,
+}
+
+#[test]
+fn skipped_fields_are_computed_from_the_set_fields() {
+    let rect = Rectangle::builder().width(2).height(3).build();
+    assert_eq!(
+        rect,
+        Some(Rectangle {
+            width: 2,
+            revision: 0,
+            height: 3,
+            area: 6
+        })
+    );
+}
+
+#[test]
+fn invariants_see_the_computed_fields() {
+    assert!(Rectangle::builder().width(0).height(3).build().is_none());
+}
+
+#[test]
+fn skipped_fields_can_refer_to_each_other() {
+    let line = Polyline::builder().points(vec![(0., 0.), (1., 1.)]).build();
+    assert_eq!(line.len, 2);
+    assert!(!line.is_degenerate);
+    assert_eq!(line.description, "polyline with 2 points");
+    assert_eq!(line.points.len(), 2);
+}
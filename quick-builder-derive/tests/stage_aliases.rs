@@ -0,0 +1,72 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(stage_aliases)]
+#[invariant(|image| image.data.len() == image.width * image.height)]
+struct Image<'a, T: Copy = u8>
+where
+    T: Default,
+{
+    width: usize,
+    height: usize,
+    data: &'a [T],
+    max_value: T,
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(stage_aliases)]
+struct Keywords {
+    r#type: i32,
+    r#in: i32,
+}
+
+// without the option, there are no aliases that could clash with these
+#[derive(Debug, PartialEq, QuickBuilder)]
+struct Plain {
+    value: i32,
+}
+
+#[allow(dead_code)]
+struct PlainBuilderEmpty;
+
+#[allow(dead_code)]
+type PlainBuilderComplete = Plain;
+
+fn with_size<'a, T: Copy + Default>(
+    builder: ImageBuilderEmpty<'a, T>,
+    size: usize,
+) -> ImageBuilderAfterHeight<'a, T> {
+    builder.width(size).height(size)
+}
+
+fn finish<T: Copy + Default>(builder: ImageBuilderComplete<'_, T>) -> Option<Image<'_, T>> {
+    builder.build()
+}
+
+#[test]
+fn stages_can_be_named_with_aliases() {
+    let data = [1, 2, 3, 4];
+    let after_data: ImageBuilderAfterData = with_size(Image::builder(), 2).data(&data);
+    let complete: ImageBuilderAfterMaxValue = after_data.max_value(4);
+    assert_eq!(
+        finish(complete),
+        Some(Image {
+            width: 2,
+            height: 2,
+            data: &data,
+            max_value: 4
+        })
+    );
+}
+
+#[test]
+fn aliases_of_raw_identifiers_drop_the_prefix() {
+    let after_type: KeywordsBuilderAfterType = Keywords::builder().r#type(1);
+    let complete: KeywordsBuilderComplete = after_type.r#in(2);
+    assert_eq!(complete.build(), Keywords { r#type: 1, r#in: 2 });
+}
+
+#[test]
+fn aliases_are_only_generated_on_request() {
+    assert_eq!(Plain::builder().value(1).build(), Plain { value: 1 });
+}
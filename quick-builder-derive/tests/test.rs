@@ -1,2 +1,16 @@
+pub mod async_invariants;
+pub mod build_variants;
+pub mod builder_derives;
+pub mod builder_paths;
+pub mod conditional_setters;
+pub mod const_builders;
+pub mod debug_only_invariants;
+pub mod generics;
+pub mod hygiene;
+pub mod phantom_data;
+pub mod skipped_fields;
+pub mod stage_aliases;
+pub mod transforms;
+pub mod validation_context;
 pub mod with_invariants;
 pub mod without_invariants;
@@ -0,0 +1,69 @@
+use quick_builder_derive::QuickBuilder;
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(normalize = normalize_range)]
+#[invariant(|this| this.lower < this.upper)]
+struct Range {
+    #[builder(transform = |name: String| name.trim().to_string())]
+    #[invariant(|name| !name.is_empty())]
+    name: String,
+    #[builder(transform = clamp_to_unit)]
+    lower: f64,
+    #[builder(transform = clamp_to_unit)]
+    upper: f64,
+}
+
+fn clamp_to_unit(value: f64) -> f64 {
+    value.clamp(0., 1.)
+}
+
+fn normalize_range(range: &mut Range) {
+    range.name = range.name.to_lowercase();
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(normalize = |this| this.words.sort())]
+struct Sorted<T: Ord> {
+    #[builder(transform = |mut words| { words.dedup(); words })]
+    words: Vec<T>,
+}
+
+#[test]
+fn transforms_are_applied_in_the_setters() {
+    let range = Range::builder()
+        .name("  Unit  ".into())
+        .lower(-1.)
+        .upper(0.5)
+        .build();
+    assert_eq!(
+        range,
+        Some(Range {
+            name: "unit".into(),
+            lower: 0.,
+            upper: 0.5
+        })
+    );
+}
+
+#[test]
+fn invariants_are_checked_after_transforms() {
+    let empty_name = Range::builder()
+        .name("   ".into())
+        .lower(0.)
+        .upper(1.)
+        .build();
+    assert_eq!(empty_name, None);
+    // both are clamped to 1
+    let clamped = Range::builder()
+        .name("a".into())
+        .lower(2.)
+        .upper(3.)
+        .build();
+    assert_eq!(clamped, None);
+}
+
+#[test]
+fn normalize_runs_on_the_finished_struct() {
+    let sorted = Sorted::builder().words(vec![3, 3, 1, 2, 2]).build();
+    assert_eq!(sorted.words, vec![1, 2, 3]);
+}
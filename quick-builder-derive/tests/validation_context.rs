@@ -0,0 +1,106 @@
+use quick_builder_derive::QuickBuilder;
+use std::collections::HashSet;
+
+struct Registry {
+    known_ids: HashSet<u32>,
+    max_retries: u8,
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(context = Registry)]
+#[invariant(|job, registry| job.parent != Some(job.id) || registry.known_ids.is_empty())]
+struct Job {
+    #[invariant(|id, registry| registry.known_ids.contains(id))]
+    id: u32,
+    #[invariant(checks::is_known_parent)]
+    parent: Option<u32>,
+    #[invariant(|retries, parent, registry| parent.is_some() || *retries <= registry.max_retries)]
+    retries: u8,
+}
+
+mod checks {
+    use super::Registry;
+
+    pub fn is_known_parent(parent: &Option<u32>, registry: &Registry) -> bool {
+        parent.is_none_or(|parent| registry.known_ids.contains(&parent))
+    }
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(context = std::ops::Range<T>)]
+struct InRange<T: PartialOrd> {
+    #[invariant(|value, range| range.contains(value))]
+    value: T,
+}
+
+fn registry() -> Registry {
+    Registry {
+        known_ids: [1, 2, 3].into_iter().collect(),
+        max_retries: 3,
+    }
+}
+
+#[test]
+fn invariants_receive_the_context() {
+    let registry = registry();
+    let job = Job::builder()
+        .id(1)
+        .parent(Some(2))
+        .retries(10)
+        .build_with(&registry);
+    assert_eq!(
+        job,
+        Some(Job {
+            id: 1,
+            parent: Some(2),
+            retries: 10
+        })
+    );
+
+    let unknown_id = Job::builder()
+        .id(4)
+        .parent(None)
+        .retries(0)
+        .build_with(&registry);
+    assert_eq!(unknown_id, None);
+
+    let unknown_parent = Job::builder()
+        .id(1)
+        .parent(Some(4))
+        .retries(0)
+        .build_with(&registry);
+    assert_eq!(unknown_parent, None);
+
+    let too_many_retries = Job::builder()
+        .id(1)
+        .parent(None)
+        .retries(4)
+        .build_with(&registry);
+    assert_eq!(too_many_retries, None);
+
+    let own_parent = Job::builder()
+        .id(1)
+        .parent(Some(1))
+        .retries(0)
+        .build_with(&registry);
+    assert_eq!(own_parent, None);
+}
+
+#[test]
+fn context_can_depend_on_generics() {
+    assert!(InRange::builder().value(5).build_with(&(0..10)).is_some());
+    assert!(InRange::builder()
+        .value(0.5)
+        .build_with(&(1.0..2.0))
+        .is_none());
+}
+
+#[test]
+#[should_panic(expected = "job should be valid: invariant of field `id` of `Job` is violated")]
+fn build_expect_with_takes_the_context() {
+    let _ = Job::builder()
+        .id(5)
+        .parent(None)
+        .retries(0)
+        .build_with_expect(&registry(), "job should be valid");
+}
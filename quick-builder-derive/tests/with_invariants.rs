@@ -33,6 +33,25 @@ where
     data: &'a mut [T],
 }
 
+#[derive(Debug, PartialEq, QuickBuilder)]
+struct Window<'a> {
+    title: &'a str,
+    max_height: u32,
+    #[invariant(|h, max_height| h <= max_height)]
+    height: u32,
+    #[invariant(|w, height, title| *w >= *height && title.len() as u32 <= *w)]
+    width: u32,
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(dyn_builder)]
+struct Buffer<'a> {
+    #[invariant(|data| !data.is_empty())]
+    data: &'a mut [u8],
+    #[invariant(|len, data| *len <= data.len())]
+    len: usize,
+}
+
 mod checks {
     pub fn check_foo<T: Default + Ord>(t: &T) -> bool {
         t < &Default::default()
@@ -137,3 +156,53 @@ fn sad_path_for_builder_with_generics_and_lifetimes() {
         .build();
     assert_eq!(built, None);
 }
+
+#[test]
+fn field_invariants_can_refer_to_previously_set_fields() {
+    let built = Window::builder()
+        .title("hello")
+        .max_height(10)
+        .height(10)
+        .width(20)
+        .build();
+    assert_eq!(
+        built,
+        Some(Window {
+            title: "hello",
+            max_height: 10,
+            height: 10,
+            width: 20
+        })
+    );
+
+    let too_high = Window::builder()
+        .title("hello")
+        .max_height(10)
+        .height(11)
+        .width(20)
+        .build();
+    assert_eq!(too_high, None);
+
+    let too_narrow_for_title = Window::builder()
+        .title("hello")
+        .max_height(10)
+        .height(2)
+        .width(4)
+        .build();
+    assert_eq!(too_narrow_for_title, None);
+}
+
+#[test]
+fn invariants_on_mutable_references() {
+    let mut data = [1, 2, 3];
+    let built = Buffer::builder().data(&mut data).len(2).build();
+    assert_eq!(built.map(|buffer| buffer.len), Some(2));
+    assert_eq!(Buffer::builder().data(&mut []).len(0).build(), None);
+    assert_eq!(Buffer::builder().data(&mut data).len(4).build(), None);
+
+    let mut builder = Buffer::dyn_builder();
+    builder.data(&mut data).len(3);
+    let buffer = builder.try_build().unwrap();
+    buffer.data[0] = 4;
+    assert_eq!(data, [4, 2, 3]);
+}
@@ -0,0 +1,37 @@
+/// Implemented by all structs that derive `QuickBuilder`, so that generic
+/// code can get a builder for a type.
+///
+/// ```rust
+/// use quick_builder::{Buildable, QuickBuilder};
+///
+/// #[derive(QuickBuilder)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// fn builder_for<T: Buildable>() -> T::Builder {
+///     T::builder()
+/// }
+///
+/// fn main() {
+///     let point = builder_for::<Point>().x(1).y(2).build();
+///     assert_eq!(point.x, 1);
+/// }
+/// ```
+pub trait Buildable: Sized {
+    /// the builder before any field is set
+    type Builder;
+    /// the result of building the complete builder. This is the struct
+    /// itself, or an `Option` of it if the struct has invariants.
+    ///
+    /// That is the return type of `build()`, except for two cases: builders
+    /// with a validation context return it from `build_with(&context)`, and
+    /// builders with async invariants return a future with this output from
+    /// `build()`.
+    type Output;
+
+    /// create a builder where no fields are set yet, just like the
+    /// `builder()` function of the struct.
+    fn builder() -> Self::Builder;
+}
@@ -0,0 +1,68 @@
+#[cfg(feature = "alloc")]
+use crate::__private::alloc::string::String;
+use core::fmt;
+
+/// The error returned by the `try_build()` function of the dynamic builders,
+/// which are generated with `#[quick_builder(dyn_builder)]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DynBuildError {
+    /// the field with the given name has not been set
+    MissingField(&'static str),
+    /// an invariant is violated, the payload is a description of the invariant
+    InvariantViolated(&'static str),
+}
+
+impl fmt::Display for DynBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DynBuildError::MissingField(field) => write!(f, "field `{field}` has not been set"),
+            DynBuildError::InvariantViolated(description) => f.write_str(description),
+        }
+    }
+}
+
+impl core::error::Error for DynBuildError {}
+
+/// The error returned by the `set_str()` function of the dynamic builders,
+/// which is generated with `#[quick_builder(dyn_builder, set_str)]`. This
+/// needs the `alloc` feature. Fields whose types don't implement `FromStr`
+/// can opt out of `set_str` with `#[builder(no_set_str)]`, then setting them
+/// gives `SetError::Unsupported`.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SetError {
+    /// there is no field with the given name that can be set
+    UnknownField(String),
+    /// the value for the field could not be parsed
+    Parse {
+        /// the name of the field
+        field: &'static str,
+        /// the error message of the parser
+        message: String,
+    },
+    /// an invariant of the field is violated, the payload is a description of
+    /// the invariant
+    InvariantViolated(&'static str),
+    /// the field with the given name can't be set from a string, because it
+    /// is marked with `#[builder(no_set_str)]`
+    Unsupported(&'static str),
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for SetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetError::UnknownField(field) => write!(f, "there is no field `{field}`"),
+            SetError::Parse { field, message } => {
+                write!(f, "could not parse value for field `{field}`: {message}")
+            }
+            SetError::InvariantViolated(description) => f.write_str(description),
+            SetError::Unsupported(field) => {
+                write!(f, "field `{field}` can't be set from a string")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl core::error::Error for SetError {}
@@ -1,5 +1,143 @@
+#![no_std]
 #![allow(clippy::needless_doctest_main)]
 #![doc= include_str!("../Readme.md")]
 #![warn(missing_docs)]
 
+pub use buildable::Buildable;
+pub use error::DynBuildError;
+#[cfg(feature = "alloc")]
+pub use error::SetError;
 pub use quick_builder_derive::QuickBuilder;
+pub use stages::{HasField, IsComplete, SetField};
+
+/// the crates and types that the generated code refers to. This is not public
+/// API.
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "alloc")]
+    pub extern crate alloc;
+    #[cfg(feature = "arbitrary")]
+    pub use arbitrary;
+    #[cfg(feature = "proptest")]
+    pub use proptest;
+
+    pub use crate::state::State;
+
+    pub use crate::__requires_alloc as requires_alloc;
+    pub use crate::__requires_arbitrary as requires_arbitrary;
+    pub use crate::__requires_proptest as requires_proptest;
+}
+
+/// passes the items through if the `alloc` feature is enabled, which the
+/// derive can't check itself. This is not public API.
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __requires_alloc {
+    ($($items:tt)*) => {
+        $($items)*
+    };
+}
+
+/// a compile error that names the missing `alloc` feature. This is not public
+/// API.
+#[cfg(not(feature = "alloc"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __requires_alloc {
+    ($($items:tt)*) => {
+        ::core::compile_error!(
+            "`set_str` needs the `alloc` feature of quick-builder, which is a default feature"
+        );
+    };
+}
+
+/// passes the items through if the `proptest` feature is enabled, see
+/// `__requires_alloc`. This is not public API.
+#[cfg(feature = "proptest")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __requires_proptest {
+    ($($items:tt)*) => {
+        $($items)*
+    };
+}
+
+/// a compile error that names the missing `proptest` feature. This is not
+/// public API.
+#[cfg(not(feature = "proptest"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __requires_proptest {
+    ($($items:tt)*) => {
+        ::core::compile_error!("`proptest` needs the `proptest` feature of quick-builder");
+    };
+}
+
+/// passes the items through if the `arbitrary` feature is enabled, see
+/// `__requires_alloc`. This is not public API.
+#[cfg(feature = "arbitrary")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __requires_arbitrary {
+    ($($items:tt)*) => {
+        $($items)*
+    };
+}
+
+/// a compile error that names the missing `arbitrary` feature. This is not
+/// public API.
+#[cfg(not(feature = "arbitrary"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __requires_arbitrary {
+    ($($items:tt)*) => {
+        ::core::compile_error!("`arbitrary` needs the `arbitrary` feature of quick-builder");
+    };
+}
+
+mod buildable;
+mod error;
+mod stages;
+mod state;
+
+/// Build a value from a finished builder with invariants in a const context,
+/// such that violated invariants are a compile error.
+///
+/// The given expression must be a builder where all fields have been set and
+/// whose `build()` function returns an `Option`. If the invariants are violated,
+/// this macro panics, which is a compile error in const contexts. See the
+/// crate documentation for details on which builders can be used in const
+/// contexts.
+///
+/// ```rust
+/// use quick_builder::{const_build, QuickBuilder};
+///
+/// #[derive(QuickBuilder)]
+/// struct Range {
+///     #[invariant(is_positive, const)]
+///     start: u32,
+///     end: u32,
+/// }
+///
+/// const fn is_positive(value: &u32) -> bool {
+///     *value > 0
+/// }
+///
+/// const RANGE: Range = const_build!(Range::builder().start(1).end(10));
+///
+/// fn main() {
+///     assert_eq!(RANGE.start, 1);
+/// }
+/// ```
+#[macro_export]
+macro_rules! const_build {
+    ($builder:expr) => {
+        match $builder.build() {
+            ::core::option::Option::Some(value) => value,
+            ::core::option::Option::None => {
+                ::core::panic!("const_build!: the invariants of the built value are violated")
+            }
+        }
+    };
+}
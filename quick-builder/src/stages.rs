@@ -0,0 +1,43 @@
+//! traits for writing generic code over the stages of the typestate builders.
+//!
+//! The derive implements them for the builder in each of its stages if the
+//! struct has the `#[quick_builder(stage_traits)]` option. The fields are
+//! identified by marker types, which are generated in a module next to the
+//! struct. For a struct `Foo` with a field `bar`, the marker is
+//! `FooFields::bar`.
+
+/// Implemented by the builder in all stages where the field with the marker
+/// `F` has been set.
+pub trait HasField<F> {
+    /// the type of the field
+    type Value;
+
+    /// the value of the field that has been set
+    fn field(&self) -> &Self::Value;
+}
+
+/// Implemented by the builder in the stage where the field with the marker
+/// `F` is the next one to set.
+pub trait SetField<F> {
+    /// the type of the field
+    type Value;
+    /// the builder after the field has been set
+    type Next;
+
+    /// set the field, just like the setter of the field
+    fn set(self, value: Self::Value) -> Self::Next;
+}
+
+/// Implemented by the builder in the stage where all fields have been set.
+///
+/// Builders with a validation context or with async invariants don't
+/// implement this trait, because they need the context or return a future
+/// when building.
+pub trait IsComplete {
+    /// the return type of the build function. This is the struct itself,
+    /// or an `Option` of it if the struct has invariants.
+    type Output;
+
+    /// build the instance, just like the `build()` function of the builder
+    fn build(self) -> Self::Output;
+}
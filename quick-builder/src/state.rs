@@ -0,0 +1,37 @@
+use core::mem::ManuallyDrop;
+
+/// The values of the fields that have been set on a typestate builder.
+///
+/// The builder is declared next to the struct, so its fields are visible in
+/// the module of the struct. The values are private in here, so that code in
+/// that module can't get at them or make up a builder in an arbitrary state.
+/// This is not public API.
+#[doc(hidden)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct State<S>(S);
+
+impl<S> State<S> {
+    /// wrap the values of the fields
+    pub const fn new(values: S) -> Self {
+        Self(values)
+    }
+
+    /// the values of the fields
+    pub const fn get(&self) -> &S {
+        &self.0
+    }
+
+    /// unwrap the values of the fields.
+    ///
+    /// Moving the values out of a generic type is not allowed in const
+    /// functions, because the compiler can't tell that nothing is left to drop.
+    /// So we read the values from behind a `ManuallyDrop` instead.
+    pub const fn into_inner(self) -> S {
+        let this = ManuallyDrop::new(self);
+        // Safety: the struct is repr(transparent), so it has the same layout
+        // as the values, and the values are never dropped in place because
+        // they are wrapped in a ManuallyDrop.
+        unsafe { core::ptr::read(&this as *const ManuallyDrop<Self> as *const S) }
+    }
+}
@@ -0,0 +1,83 @@
+use quick_builder::{Buildable, IsComplete, QuickBuilder, SetField};
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(stage_traits)]
+struct Counter {
+    count: u32,
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(stage_traits)]
+struct Limit<T: Copy> {
+    #[invariant(|limit| *limit < 10)]
+    limit: u32,
+    value: T,
+}
+
+struct Bounds {
+    max: u32,
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(context = Bounds)]
+struct Bounded {
+    #[invariant(|value, bounds| *value <= bounds.max)]
+    value: u32,
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+struct Remote {
+    #[invariant(async is_known)]
+    id: u32,
+}
+
+async fn is_known(id: &u32) -> bool {
+    *id != 0
+}
+
+/// only compiles if the output of building is `Option<T>`
+fn assert_optional_output<T: Buildable<Output = Option<T>>>() {}
+
+/// a fixture that builds any type with a single `u32` field from its value
+fn fixture<T, F>(value: u32) -> <T::Builder as SetField<F>>::Next
+where
+    T: Buildable,
+    T::Builder: SetField<F, Value = u32>,
+{
+    T::builder().set(value)
+}
+
+fn build<T>(builder: impl IsComplete<Output = T::Output>) -> T::Output
+where
+    T: Buildable,
+{
+    builder.build()
+}
+
+#[test]
+fn generic_code_can_get_builders() {
+    let counter = build::<Counter>(fixture::<Counter, _>(1));
+    assert_eq!(counter, Counter { count: 1 });
+
+    let limit = build::<Limit<char>>(fixture::<Limit<char>, _>(1).value('a'));
+    assert_eq!(
+        limit,
+        Some(Limit {
+            limit: 1,
+            value: 'a'
+        })
+    );
+    let limit = build::<Limit<char>>(fixture::<Limit<char>, _>(10).value('a'));
+    assert_eq!(limit, None);
+}
+
+#[test]
+fn output_is_the_result_of_building_with_context_or_async_invariants() {
+    assert_optional_output::<Bounded>();
+    assert_optional_output::<Remote>();
+    let bounds = Bounds { max: 1 };
+    let bounded: <Bounded as Buildable>::Output = Bounded::builder().value(1).build_with(&bounds);
+    assert_eq!(bounded, Some(Bounded { value: 1 }));
+    // we don't run the future, it's enough that it compiles
+    drop(Remote::builder().id(1).build());
+}
@@ -0,0 +1,126 @@
+//! the generated code must not cause any warnings in the crates that use it,
+//! so this crate denies all warnings (and clippy lints) and derives builders
+//! for structs with generics, lifetimes and invariants. This is also run
+//! with `--release`, where the debug_only invariants must still count as used.
+#![deny(warnings, missing_docs, unused_qualifications, rust_2018_idioms)]
+#![deny(clippy::all)]
+
+use std::fmt::Debug;
+
+use quick_builder::QuickBuilder;
+
+/// a struct with lifetimes, type generics with defaults and const generics
+#[derive(Debug, QuickBuilder)]
+#[quick_builder(derive(Clone, Debug))]
+#[invariant(|view| view.data.len() == view.offset + N)]
+pub struct View<'a, T: Debug, const N: usize, U = i32>
+where
+    T: Clone,
+{
+    /// the data that we view into
+    pub data: &'a [T],
+    /// where the view starts
+    #[invariant(|offset| *offset < 100)]
+    pub offset: usize,
+    /// some other value
+    #[builder(transform = |other: U| other)]
+    pub other: U,
+}
+
+/// a struct with cross-field invariants, skipped fields and normalization
+#[derive(Debug, QuickBuilder)]
+#[quick_builder(normalize = Rect::normalize, dyn_builder, set_str)]
+pub struct Rect {
+    /// the width
+    #[invariant(|w| *w > 0)]
+    pub width: u32,
+    /// the height, which must not be larger than the width
+    #[invariant(|h, width| *h <= *width)]
+    pub height: u32,
+    /// the area, which is computed from the other fields
+    #[builder(skip = width * height)]
+    pub area: u32,
+    // a private field without docs, whose dyn setter must be documented anyway
+    z_index: i32,
+}
+
+impl Rect {
+    fn normalize(&mut self) {
+        self.area = self.area.max(1);
+    }
+}
+
+/// the context for the invariants of `Bounded`
+pub struct Limits {
+    /// the maximum value
+    pub max: i64,
+}
+
+/// a struct with a validation context and invariants that are only checked
+/// in debug builds
+#[derive(Debug, QuickBuilder)]
+#[quick_builder(context = Limits)]
+pub struct Bounded<'a> {
+    /// the value
+    #[invariant(|value, limits| *value <= limits.max)]
+    pub value: i64,
+    /// a name
+    #[invariant(has_name, debug_only)]
+    pub name: &'a str,
+}
+
+fn has_name(name: &str, _limits: &Limits) -> bool {
+    !name.is_empty()
+}
+
+/// a struct with const invariants
+#[derive(Debug, QuickBuilder)]
+#[invariant(Range::is_valid, const)]
+pub struct Range {
+    /// the start
+    pub start: u8,
+    /// the end
+    pub end: u8,
+}
+
+impl Range {
+    const fn is_valid(&self) -> bool {
+        self.start <= self.end
+    }
+}
+
+/// a struct with an async invariant
+#[derive(Debug, QuickBuilder)]
+pub struct Remote {
+    /// the id, which must exist somewhere
+    #[invariant(async exists)]
+    pub id: u32,
+}
+
+async fn exists(id: &u32) -> bool {
+    *id != 0
+}
+
+#[test]
+fn generated_code_is_warning_free() {
+    let data = [1, 2, 3];
+    let view = View::<'_, i32, 2>::builder()
+        .data(&data)
+        .offset(1)
+        .other(5)
+        .build();
+    assert!(view.is_some());
+
+    let rect = Rect::builder().width(2).height(1).z_index(0).build();
+    assert_eq!(rect.map(|r| (r.area, r.z_index)), Some((2, 0)));
+
+    let limits = Limits { max: 10 };
+    let bounded = Bounded::builder().value(5).name("x").build_with(&limits);
+    assert!(bounded.is_some());
+
+    const RANGE: Range = quick_builder::const_build!(Range::builder().start(1).end(2));
+    assert_eq!(RANGE.end, 2);
+
+    // we don't run the future, it's enough that it compiles
+    drop(Remote::builder().id(1).build());
+}
@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+
+use quick_builder::{DynBuildError, QuickBuilder, SetError};
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(dyn_builder)]
+#[invariant(|rect| rect.width * rect.height == rect.area)]
+struct Rectangle {
+    #[invariant(|w| *w > 0)]
+    width: u32,
+    #[builder(transform = |h: u32| h.max(1))]
+    height: u32,
+    #[builder(skip = width * height)]
+    area: u32,
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+// the label can't be parsed from a string, so set_str is not available, but
+// that must not be a compile error
+#[quick_builder(dyn_builder, set_str)]
+struct Labeled<'a, T> {
+    label: &'a str,
+    value: T,
+}
+
+// the label opts out of set_str, so the other fields can still be parsed
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(dyn_builder, set_str)]
+struct Entry<'a> {
+    #[builder(no_set_str)]
+    label: &'a str,
+    value: u32,
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(dyn_builder)]
+struct Buffer<T = u8, const N: usize = 4> {
+    #[invariant(|data| data.len() <= N)]
+    data: Vec<T>,
+}
+
+struct Limits {
+    max: u32,
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(dyn_builder, context = Limits)]
+struct Bounded {
+    #[invariant(|value, limits| *value <= limits.max)]
+    value: u32,
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(dyn_builder, set_str)]
+struct Window {
+    #[invariant(|w| *w >= 100)]
+    width: u32,
+    #[invariant(|h, width| h <= width)]
+    height: u32,
+    #[builder(transform = |title: String| title.trim().to_string())]
+    title: String,
+    #[builder(skip = false)]
+    maximized: bool,
+}
+
+#[test]
+fn fields_can_be_set_in_any_order() {
+    let mut builder = Rectangle::dyn_builder();
+    builder.height(0);
+    builder.width(2);
+    assert_eq!(
+        builder.try_build(),
+        Ok(Rectangle {
+            width: 2,
+            height: 1,
+            area: 2
+        })
+    );
+}
+
+#[test]
+fn missing_fields_are_reported_by_name() {
+    let mut builder = Rectangle::dyn_builder();
+    builder.height(1);
+    assert_eq!(
+        builder.try_build(),
+        Err(DynBuildError::MissingField("width"))
+    );
+    assert_eq!(
+        DynBuildError::MissingField("width").to_string(),
+        "field `width` has not been set"
+    );
+}
+
+#[test]
+fn invariants_are_shared_with_the_typestate_builder() {
+    let mut builder = Rectangle::dyn_builder();
+    builder.width(0).height(1);
+    assert_eq!(
+        builder.try_build(),
+        Err(DynBuildError::InvariantViolated(
+            "invariant of field `width` of `Rectangle` is violated"
+        ))
+    );
+}
+
+#[test]
+fn builders_can_be_filled_from_key_value_input() {
+    let input: HashMap<&str, &str> = [("label", "answer"), ("value", "42")].into();
+    let mut builder = Labeled::dyn_builder();
+    for (key, value) in &input {
+        match *key {
+            "label" => builder.label(value),
+            "value" => builder.value(value.parse::<i32>().unwrap()),
+            _ => unreachable!(),
+        };
+    }
+    assert_eq!(
+        builder.try_build(),
+        Ok(Labeled {
+            label: "answer",
+            value: 42
+        })
+    );
+}
+
+#[test]
+fn context_is_passed_to_try_build_with() {
+    let limits = Limits { max: 10 };
+    let mut builder = Bounded::dyn_builder();
+    builder.value(11);
+    assert!(matches!(
+        builder.try_build_with(&limits),
+        Err(DynBuildError::InvariantViolated(_))
+    ));
+}
+
+#[test]
+fn fields_can_be_set_from_strings() {
+    let mut builder = Window::dyn_builder();
+    builder.set_str("height", "480").unwrap();
+    builder.set_str("title", "  main ").unwrap();
+    builder.set_str("width", "640").unwrap();
+    assert_eq!(
+        builder.try_build(),
+        Ok(Window {
+            width: 640,
+            height: 480,
+            title: "main".into(),
+            maximized: false,
+        })
+    );
+}
+
+#[test]
+fn set_str_reports_errors() {
+    let mut builder = Window::dyn_builder();
+    assert_eq!(
+        builder.set_str("depth", "1"),
+        Err(SetError::UnknownField("depth".into()))
+    );
+    // skipped fields can't be set
+    assert_eq!(
+        builder.set_str("maximized", "true"),
+        Err(SetError::UnknownField("maximized".into()))
+    );
+    assert!(matches!(
+        builder.set_str("width", "wide"),
+        Err(SetError::Parse { field: "width", .. })
+    ));
+    assert_eq!(
+        builder.set_str("width", "99"),
+        Err(SetError::InvariantViolated(
+            "invariant of field `width` of `Window` is violated"
+        ))
+    );
+    // invariants that depend on other fields are checked when building
+    builder.set_str("width", "100").unwrap();
+    builder.set_str("height", "200").unwrap();
+    builder.set_str("title", "title").unwrap();
+    assert_eq!(
+        builder.try_build(),
+        Err(DynBuildError::InvariantViolated(
+            "invariant of field `height` of `Window` is violated"
+        ))
+    );
+}
+
+#[test]
+fn fields_can_opt_out_of_set_str() {
+    let mut builder = Entry::dyn_builder();
+    assert_eq!(
+        builder.set_str("label", "name"),
+        Err(SetError::Unsupported("label"))
+    );
+    builder.set_str("value", "5").unwrap();
+    builder.label("name");
+    assert_eq!(
+        builder.try_build(),
+        Ok(Entry {
+            label: "name",
+            value: 5
+        })
+    );
+}
+
+#[test]
+fn dyn_builders_keep_generic_defaults() {
+    // in type position, the defaults apply
+    let mut builder = <Buffer>::dyn_builder();
+    builder.data(vec![1, 2, 3, 4]);
+    assert_eq!(
+        builder.try_build(),
+        Ok(Buffer {
+            data: vec![1, 2, 3, 4]
+        })
+    );
+
+    let mut builder = Buffer::<u16, 2>::dyn_builder();
+    builder.data(vec![1, 2, 3]);
+    assert!(matches!(
+        builder.try_build(),
+        Err(DynBuildError::InvariantViolated(_))
+    ));
+}
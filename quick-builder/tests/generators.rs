@@ -0,0 +1,68 @@
+use arbitrary::{Arbitrary, Unstructured};
+use proptest::prelude::*;
+use quick_builder::QuickBuilder;
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(proptest, arbitrary)]
+#[invariant(|range| range.start <= range.end)]
+struct Range {
+    start: u8,
+    end: u8,
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(proptest)]
+struct Percentage {
+    // the strategy only generates valid values, so nothing is rejected
+    #[builder(strategy = 0..=100u8)]
+    #[invariant(|value| *value <= 100)]
+    value: u8,
+    #[builder(transform = |label: String| label.to_uppercase())]
+    label: String,
+    #[builder(skip = value == 100)]
+    is_full: bool,
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(proptest, arbitrary)]
+struct Pair<T> {
+    first: T,
+    second: T,
+}
+
+proptest! {
+    #[test]
+    fn proptest_only_generates_valid_instances(range in any::<Range>()) {
+        prop_assert!(range.start <= range.end);
+    }
+
+    #[test]
+    fn proptest_uses_the_builder(percentage in any::<Percentage>()) {
+        prop_assert!(percentage.value <= 100);
+        prop_assert_eq!(percentage.label.to_uppercase(), percentage.label.clone());
+        prop_assert_eq!(percentage.is_full, percentage.value == 100);
+    }
+
+    #[test]
+    fn proptest_works_with_generics(pair in any::<Pair<Range>>()) {
+        prop_assert!(pair.first.start <= pair.first.end);
+        prop_assert!(pair.second.start <= pair.second.end);
+    }
+}
+
+#[test]
+fn arbitrary_rejects_invalid_instances() {
+    let valid = Range::arbitrary(&mut Unstructured::new(&[1, 2]));
+    assert_eq!(valid, Ok(Range { start: 1, end: 2 }));
+    let invalid = Range::arbitrary(&mut Unstructured::new(&[2, 1]));
+    assert_eq!(invalid, Err(arbitrary::Error::IncorrectFormat));
+
+    let pair = Pair::<u8>::arbitrary(&mut Unstructured::new(&[3, 4]));
+    assert_eq!(
+        pair,
+        Ok(Pair {
+            first: 3,
+            second: 4
+        })
+    );
+}
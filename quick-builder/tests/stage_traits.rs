@@ -0,0 +1,138 @@
+use quick_builder::{HasField, IsComplete, QuickBuilder, SetField};
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(stage_traits)]
+#[invariant(|window| window.width >= window.height)]
+struct Window {
+    title: String,
+    width: u32,
+    height: u32,
+}
+
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(stage_aliases, stage_traits)]
+struct Label {
+    title: String,
+    color: u32,
+}
+
+mod private {
+    use quick_builder::QuickBuilder;
+
+    // the type of the private field must not leak through the traits
+    #[derive(Debug)]
+    struct Secret(u32);
+
+    #[derive(Debug, QuickBuilder)]
+    #[quick_builder(stage_aliases, stage_traits)]
+    pub struct Account {
+        pub name: &'static str,
+        secret: Secret,
+    }
+
+    impl Account {
+        pub fn with_secret(builder: AccountBuilderAfterName, secret: u32) -> Account {
+            builder.secret(Secret(secret)).build()
+        }
+
+        pub fn secret(&self) -> u32 {
+            self.secret.0
+        }
+    }
+
+    #[derive(Debug, QuickBuilder)]
+    #[quick_builder(stage_traits)]
+    pub(crate) struct Restricted {
+        pub(super) value: u32,
+    }
+}
+
+// without the option, there is no module with markers that could clash
+#[derive(Debug, PartialEq, QuickBuilder)]
+struct Plain {
+    value: u32,
+}
+
+#[allow(non_snake_case, dead_code)]
+mod PlainFields {}
+
+/// sets the title on any builder where it is the next field
+fn with_title<B>(builder: B) -> B::Next
+where
+    B: SetField<WindowFields::title, Value = String>,
+{
+    builder.set("title".into())
+}
+
+/// the title of any builder where it has been set
+fn title<B: HasField<WindowFields::title, Value = String>>(builder: &B) -> &str {
+    builder.field()
+}
+
+fn build_all<B: IsComplete>(builders: Vec<B>) -> Vec<B::Output> {
+    builders.into_iter().map(IsComplete::build).collect()
+}
+
+#[test]
+fn generic_code_can_set_and_read_fields() {
+    let builder = with_title(Window::builder());
+    assert_eq!(title(&builder), "title");
+    let builder = builder.width(2);
+    assert_eq!(title(&builder), "title");
+    assert_eq!(HasField::<WindowFields::width>::field(&builder), &2);
+
+    let label = <LabelBuilderAfterTitle as SetField<LabelFields::color>>::set(
+        Label::builder().title("label".into()),
+        0xff,
+    );
+    assert_eq!(
+        IsComplete::build(label),
+        Label {
+            title: "label".into(),
+            color: 0xff
+        }
+    );
+}
+
+#[test]
+fn generic_code_can_build_complete_builders() {
+    let builders = vec![
+        with_title(Window::builder()).width(2).height(1),
+        with_title(Window::builder()).width(1).height(2),
+    ];
+    assert_eq!(
+        build_all(builders),
+        vec![
+            Some(Window {
+                title: "title".into(),
+                width: 2,
+                height: 1
+            }),
+            None
+        ]
+    );
+}
+
+#[test]
+fn traits_respect_the_visibility_of_fields() {
+    let builder = private::Account::builder().name("name");
+    assert_eq!(
+        HasField::<private::AccountFields::name>::field(&builder),
+        &"name"
+    );
+    let account = private::Account::with_secret(builder, 42);
+    assert_eq!(account.name, "name");
+    assert_eq!(account.secret(), 42);
+
+    let restricted = private::RestrictedBuilder::new().value(1);
+    assert_eq!(
+        HasField::<private::RestrictedFields::value>::field(&restricted),
+        &1
+    );
+    assert_eq!(restricted.build().value, 1);
+}
+
+#[test]
+fn traits_are_only_implemented_on_request() {
+    assert_eq!(Plain::builder().value(1).build(), Plain { value: 1 });
+}
@@ -1,6 +1,6 @@
 #![allow(dead_code)]
 #![deny(proc_macro_derive_resolution_fallback)]
-use quick_builder::QuickBuilder;
+use quick_builder::{const_build, QuickBuilder};
 
 #[derive(QuickBuilder)]
 pub struct Foo {
@@ -27,6 +27,22 @@ pub struct Baz {
     second: i8,
 }
 
+// verify that the const_build! macro works
+#[derive(QuickBuilder)]
+#[invariant(Qux::is_valid, const)]
+pub struct Qux {
+    first: u32,
+    second: u32,
+}
+
+impl Qux {
+    const fn is_valid(&self) -> bool {
+        self.first < self.second
+    }
+}
+
+const QUX: Qux = const_build!(Qux::builder().first(1).second(2));
+
 #[test]
 // the actual tests of the macro are in the integration tests of the
 // quick-builder-derive-crate. This test just confirms we can use the
@@ -39,4 +55,6 @@ fn integration_test_works() {
     let baz = Baz::builder().first(1).second(2).build().unwrap();
     let _first: u32 = baz.first();
     let _second: &i8 = baz.second();
+    assert_eq!(QUX.first, 1);
+    assert_eq!(QUX.second, 2);
 }
@@ -0,0 +1,39 @@
+//! this crate makes sure that the generated code works if quick-builder is a
+//! renamed dependency, or is re-exported from another crate. The derive is
+//! told where to find the runtime crate with `#[quick_builder(crate = ...)]`.
+
+use qb::QuickBuilder;
+
+/// a struct that uses all parts of the runtime crate
+#[derive(Debug, Clone, PartialEq, QuickBuilder)]
+#[quick_builder(
+    crate = qb,
+    derive(Clone, Debug),
+    dyn_builder,
+    set_str,
+    stage_traits,
+    arbitrary
+)]
+#[invariant(|span| span.start <= span.end)]
+pub struct Span {
+    /// the start
+    #[invariant(|start| *start < 100)]
+    pub start: u32,
+    /// the end
+    pub end: u32,
+}
+
+/// the runtime crate can also be given as a path to a re-export
+pub mod reexport {
+    pub use qb as runtime;
+}
+
+/// a struct that finds the runtime crate through a re-export
+#[derive(Debug, PartialEq, QuickBuilder)]
+#[quick_builder(crate = crate::reexport::runtime, stage_traits)]
+pub struct Point {
+    /// the x coordinate
+    pub x: i32,
+    /// the y coordinate
+    pub y: i32,
+}
@@ -0,0 +1,36 @@
+use arbitrary::{Arbitrary, Unstructured};
+use qb::{Buildable, DynBuildError, HasField, IsComplete, SetError};
+use renamed_dep_test::{Point, Span, SpanFields};
+
+#[test]
+fn builders_work_with_a_renamed_runtime_crate() {
+    let builder = Span::builder().start(1);
+    assert_eq!(HasField::<SpanFields::start>::field(&builder), &1);
+    assert_eq!(
+        IsComplete::build(builder.end(2)),
+        Some(Span { start: 1, end: 2 })
+    );
+
+    let mut builder = Span::dyn_builder();
+    assert_eq!(builder.set_str("start", "3"), Ok(()));
+    assert!(matches!(
+        builder.set_str("end", "x"),
+        Err(SetError::Parse { field: "end", .. })
+    ));
+    assert_eq!(builder.try_build(), Err(DynBuildError::MissingField("end")));
+}
+
+#[test]
+fn builders_work_with_a_reexported_runtime_crate() {
+    let point = <Point as Buildable>::builder().x(1).y(2).build();
+    assert_eq!(point, Point { x: 1, y: 2 });
+}
+
+#[test]
+fn generators_work_with_a_renamed_runtime_crate() {
+    let bytes = [7u8; 64];
+    let mut unstructured = Unstructured::new(&bytes);
+    if let Ok(span) = Span::arbitrary(&mut unstructured) {
+        assert!(span.start <= span.end && span.start < 100);
+    }
+}